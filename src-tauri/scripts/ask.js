@@ -1,7 +1,8 @@
 /**
  * @name ask.js
- * @version 0.1.0
+ * @version 0.1.1
  * @url https://github.com/lencx/ChatGPT/tree/main/scripts/ask.js
+ * @match https://chatgpt.com/*
  */
 
 class ChatAsk {
@@ -1,3 +1,9 @@
+/**
+ * @name deep_research.js
+ * @version 0.4.0
+ * @match https://chatgpt.com/*
+ */
+
 (function() {
   'use strict';
 
@@ -9,17 +15,96 @@
       observer: null
     },
 
+    // Fallback when no selector pack was injected; the app ships scripts/selectors.json.
     selectors: {
-      inputBox: '#prompt-textarea, [data-testid="text-input"], textarea[placeholder*="Message"]',
-      sendButton: 'button[data-testid="send-button"], button[aria-label*="Send"]',
-      deepResearchButton: '[data-testid="deep-research-button"], button:has-text("Deep Research"), [aria-label*="Deep Research"]',
-      responseContainer: '[data-message-author-role="assistant"]',
-      thinkingIndicator: '[data-testid="thinking"], .result-thinking'
+      inputBox: ['#prompt-textarea', '[data-testid="text-input"]', 'textarea[placeholder*="Message"]'],
+      sendButton: ['button[data-testid="send-button"]', 'button[aria-label*="Send"]'],
+      deepResearchButton: ['[data-testid="deep-research-button"]', '[aria-label*="Deep Research"]'],
+      responseContainer: ['[data-message-author-role="assistant"]'],
+      thinkingIndicator: ['[data-testid="thinking"]', '.result-thinking'],
+      loginButton: ['[data-testid="login-button"]'],
+      userMenu: ['[data-testid="user-menu"]', '[aria-label*="Open menu"]']
+    },
+
+    // The fallback chain for `key`, from the active selector pack.
+    selectorChain(key) {
+      const pack = window.__AITODO_SELECTORS__;
+      return pack?.selectors?.[key] || this.selectors[key] || [];
+    },
+
+    // Matches for the first selector in the chain that finds anything.
+    queryAll(key) {
+      for (const selector of this.selectorChain(key)) {
+        try {
+          const elements = document.querySelectorAll(selector);
+          if (elements.length) return elements;
+        } catch (error) {
+          console.warn('[DeepResearch] Invalid selector for', key, selector, error);
+        }
+      }
+      return [];
+    },
+
+    query(key) {
+      return this.queryAll(key)[0] || null;
+    },
+
+    // The element for `key` and the selector that found it.
+    locate(key) {
+      for (const selector of this.selectorChain(key)) {
+        try {
+          const element = document.querySelector(selector);
+          if (element) return { element, selector };
+        } catch (error) {
+          console.warn('[DeepResearch] Invalid selector for', key, selector, error);
+        }
+      }
+      return { element: null, selector: null };
+    },
+
+    findDeepResearchButton() {
+      const located = this.locate('deepResearchButton');
+      if (located.element) return located;
+
+      for (const btn of document.querySelectorAll('button')) {
+        const text = btn.textContent?.toLowerCase() || '';
+        const ariaLabel = btn.getAttribute('aria-label')?.toLowerCase() || '';
+        if (text.includes('deep research') || text.includes('research') ||
+            ariaLabel.includes('deep research') || ariaLabel.includes('research')) {
+          return { element: btn, selector: 'button text "research"' };
+        }
+      }
+      return { element: null, selector: null };
+    },
+
+    // Reports which parts of the page the automation can find, for the app's self-test.
+    selfTest() {
+      const checks = ['inputBox', 'deepResearchButton', 'sendButton', 'responseContainer'].map((key) => {
+        const { element, selector } = key === 'deepResearchButton'
+          ? this.findDeepResearchButton()
+          : this.locate(key);
+        return { key, found: !!element, selector };
+      });
+
+      return {
+        url: location.href,
+        script_loaded: true,
+        logged_in: this.isLoggedIn(),
+        pack_version: window.__AITODO_SELECTORS__?.version || null,
+        checks
+      };
     },
 
     async start(todoId, prompt) {
       if (this.state.isRunning) {
         console.log('[DeepResearch] Already running');
+        // Report it, or the app would wait on this todo until the run times out.
+        if (window.__TAURI__) {
+          window.__TAURI__.event.emit('research_failed', {
+            todoId,
+            error: 'Research already in progress'
+          });
+        }
         return { success: false, error: 'Research already in progress' };
       }
 
@@ -38,6 +123,12 @@
 
         return { success: true, startedAt: this.state.startedAt };
       } catch (error) {
+        if (window.__TAURI__) {
+          window.__TAURI__.event.emit('research_failed', {
+            todoId: this.state.todoId,
+            error: error.message
+          });
+        }
         this.reset();
         return { success: false, error: error.message };
       }
@@ -58,13 +149,13 @@
     },
 
     isLoggedIn() {
-      const loginButton = document.querySelector('[data-testid="login-button"]');
-      const userMenu = document.querySelector('[data-testid="user-menu"], [aria-label*="Open menu"]');
+      const loginButton = this.query('loginButton');
+      const userMenu = this.query('userMenu');
       return !loginButton && !!userMenu;
     },
 
     async injectPrompt(prompt) {
-      const input = await this.waitForElement(this.selectors.inputBox, 5000);
+      const input = await this.waitForElement('inputBox', 5000);
       if (!input) {
         throw new Error('Could not find input box');
       }
@@ -82,27 +173,14 @@
     },
 
     async triggerDeepResearch() {
-      let deepResearchBtn = document.querySelector(this.selectors.deepResearchButton);
-      
-      if (!deepResearchBtn) {
-        const buttons = document.querySelectorAll('button');
-        for (const btn of buttons) {
-          const text = btn.textContent?.toLowerCase() || '';
-          const ariaLabel = btn.getAttribute('aria-label')?.toLowerCase() || '';
-          if (text.includes('deep research') || text.includes('research') || 
-              ariaLabel.includes('deep research') || ariaLabel.includes('research')) {
-            deepResearchBtn = btn;
-            break;
-          }
-        }
-      }
+      const deepResearchBtn = this.findDeepResearchButton().element;
 
       if (deepResearchBtn) {
         deepResearchBtn.click();
         await this.sleep(500);
       }
 
-      const sendButton = await this.waitForElement(this.selectors.sendButton, 3000);
+      const sendButton = await this.waitForElement('sendButton', 3000);
       if (sendButton && !sendButton.disabled) {
         sendButton.click();
       } else {
@@ -113,7 +191,7 @@
           which: 13,
           bubbles: true
         });
-        document.querySelector(this.selectors.inputBox)?.dispatchEvent(enterEvent);
+        this.query('inputBox')?.dispatchEvent(enterEvent);
       }
     },
 
@@ -130,12 +208,12 @@
       this.state.observer = new MutationObserver((mutations) => {
         if (!this.state.isRunning) return;
 
-        const responseElements = document.querySelectorAll(this.selectors.responseContainer);
+        const responseElements = this.queryAll('responseContainer');
         const lastResponse = responseElements[responseElements.length - 1];
         
         if (!lastResponse) return;
 
-        const thinking = document.querySelector(this.selectors.thinkingIndicator);
+        const thinking = this.query('thinkingIndicator');
         if (thinking) {
           stableCount = 0;
           return;
@@ -175,6 +253,8 @@
           todoId: this.state.todoId,
           content: content,
           citations: citations,
+          // Converted to markdown by the app; `content` is the fallback.
+          rawHtml: responseElement.innerHTML,
           source: 'ChatGPT Deep Research',
           startedAt: this.state.startedAt
         });
@@ -196,6 +276,19 @@
 
         const tag = node.tagName.toLowerCase();
 
+        // Copy/Edit controls and their icons are not part of the answer.
+        if (tag === 'button' || tag === 'svg') return;
+
+        if (tag === 'pre') {
+          // ChatGPT puts a header with the language and the buttons above the code, so
+          // the block is emitted from its <code> rather than walked.
+          const code = node.querySelector('code');
+          const lang = code?.className?.match(/language-(\w+)/)?.[1] || '';
+          const text = (code || node).textContent.replace(/\n$/, '');
+          markdown += '\n```' + lang + '\n' + text + '\n```\n';
+          return;
+        }
+
         switch (tag) {
           case 'h1': markdown += '# '; break;
           case 'h2': markdown += '## '; break;
@@ -207,14 +300,7 @@
           case 'b': markdown += '**'; break;
           case 'em':
           case 'i': markdown += '*'; break;
-          case 'code':
-            if (node.parentElement?.tagName.toLowerCase() === 'pre') {
-              const lang = node.className?.match(/language-(\w+)/)?.[1] || '';
-              markdown += '\n```' + lang + '\n';
-            } else {
-              markdown += '`';
-            }
-            break;
+          case 'code': markdown += '`'; break;
           case 'li': 
             const isOrdered = node.parentElement?.tagName.toLowerCase() === 'ol';
             markdown += isOrdered ? '1. ' : '- ';
@@ -238,13 +324,7 @@
           case 'b': markdown += '**'; break;
           case 'em':
           case 'i': markdown += '*'; break;
-          case 'code':
-            if (node.parentElement?.tagName.toLowerCase() === 'pre') {
-              markdown += '\n```\n';
-            } else {
-              markdown += '`';
-            }
-            break;
+          case 'code': markdown += '`'; break;
           case 'li': markdown += '\n'; break;
           case 'a':
             const href = node.getAttribute('href');
@@ -278,10 +358,10 @@
       this.state.startedAt = null;
     },
 
-    async waitForElement(selector, timeout = 10000) {
+    async waitForElement(key, timeout = 10000) {
       const startTime = Date.now();
       while (Date.now() - startTime < timeout) {
-        const element = document.querySelector(selector);
+        const element = this.query(key);
         if (element) return element;
         await this.sleep(100);
       }
@@ -294,5 +374,10 @@
   };
 
   window.DeepResearch = DeepResearch;
+
+  // Pick up a selector pack loaded after the app started.
+  window.__TAURI__?.core.invoke('cmd_get_selector_pack')
+    .then((pack) => { window.__AITODO_SELECTORS__ = pack; })
+    .catch((error) => console.warn('[DeepResearch] Using the startup selector pack', error));
   console.log('[DeepResearch] Automation script loaded');
 })();
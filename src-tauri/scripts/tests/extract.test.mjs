@@ -0,0 +1,61 @@
+// Golden tests for DeepResearch.extractMarkdown and extractCitations, run in jsdom against
+// saved ChatGPT conversation turns in fixtures/. Each `<name>.html` has the expected
+// markdown in `<name>.md` and the expected citations in `<name>.citations.json`.
+//
+// To add a fixture, open the conversation in a browser, copy the outerHTML of the user
+// and assistant `<article>` turns from the devtools, save them as `fixtures/<name>.html`
+// and generate its golden files with UPDATE_GOLDEN=1.
+//
+//   pnpm test:scripts                      # compare with the golden files
+//   UPDATE_GOLDEN=1 pnpm test:scripts      # rewrite them after an intended change
+
+import assert from 'node:assert/strict';
+import { readFileSync, readdirSync, writeFileSync } from 'node:fs';
+import { dirname, join } from 'node:path';
+import { test } from 'node:test';
+import { fileURLToPath } from 'node:url';
+
+import { JSDOM, VirtualConsole } from 'jsdom';
+
+const here = dirname(fileURLToPath(import.meta.url));
+const fixtures = join(here, 'fixtures');
+const script = readFileSync(join(here, '..', 'deep_research.js'), 'utf8');
+const selectorPack = JSON.parse(readFileSync(join(here, '..', 'selectors.json'), 'utf8'));
+const update = !!process.env.UPDATE_GOLDEN;
+
+// Loads the page the way the app does: the selector pack first, then the script.
+function loadPage(html) {
+  const virtualConsole = new VirtualConsole();
+  virtualConsole.on('error', console.error);
+  const { window } = new JSDOM(html, {
+    url: 'https://chatgpt.com/c/fixture',
+    runScripts: 'outside-only',
+    virtualConsole,
+  });
+  window.__AITODO_SELECTORS__ = selectorPack;
+  window.eval(script);
+  return window;
+}
+
+function golden(file, actual) {
+  const path = join(fixtures, file);
+  if (update) {
+    writeFileSync(path, actual);
+    return;
+  }
+  assert.equal(actual, readFileSync(path, 'utf8'), `${file} differs from the golden file`);
+}
+
+for (const fixture of readdirSync(fixtures).filter((file) => file.endsWith('.html'))) {
+  const name = fixture.slice(0, -'.html'.length);
+
+  test(`extracts ${name}`, () => {
+    const { DeepResearch } = loadPage(readFileSync(join(fixtures, fixture), 'utf8'));
+    const responses = DeepResearch.queryAll('responseContainer');
+    assert.ok(responses.length, 'fixture has no assistant response');
+    const response = responses[responses.length - 1];
+
+    golden(`${name}.md`, DeepResearch.extractMarkdown(response) + '\n');
+    golden(`${name}.citations.json`, JSON.stringify(DeepResearch.extractCitations(response), null, 2) + '\n');
+  });
+}
@@ -0,0 +1,462 @@
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use log::info;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::{collections::BTreeMap, fs, path::Path};
+use uuid::Uuid;
+
+use crate::core::{
+    db,
+    error::AppError,
+    research::ResearchResult,
+    todo::{recurrence_to_json, row_to_todo, tags_to_json, Todo, TODO_COLUMNS},
+};
+
+pub static BACKUP_FORMAT: &str = "aitodo-backup";
+pub static BACKUP_VERSION: u32 = 1;
+
+const SECTIONS: [&str; 3] = ["todos", "research_results", "settings"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Setting {
+    pub key: String,
+    pub value: Option<String>,
+}
+
+/// A research result as stored in the archive, including the converter that produced
+/// its content.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupResearchResult {
+    #[serde(flatten)]
+    pub result: ResearchResult,
+    /// Missing in archives written before the column existed; those rows get re-rendered.
+    #[serde(default)]
+    pub converter_version: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub format: String,
+    pub version: u32,
+    pub app_version: String,
+    pub created_at: String,
+    /// Number of records per section.
+    pub counts: BTreeMap<String, usize>,
+    /// Hex-encoded SHA-256 of each section's canonical JSON.
+    pub checksums: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupArchive {
+    pub manifest: BackupManifest,
+    pub todos: Vec<Todo>,
+    pub research_results: Vec<BackupResearchResult>,
+    pub settings: Vec<Setting>,
+    /// Contents of `config.json` at backup time.
+    pub app_conf: Option<Value>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RestoreMode {
+    /// Upsert every record by id, keeping anything not present in the archive.
+    #[default]
+    Merge,
+    /// Wipe todos, research results and settings before importing the archive.
+    Replace,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestoreReport {
+    pub mode: RestoreMode,
+    pub todos: usize,
+    pub research_results: usize,
+    pub settings: usize,
+    pub app_conf: Option<Value>,
+}
+
+/// Reads every table into a versioned archive.
+pub fn create_backup(conn: &Connection, app_conf: Option<Value>) -> Result<BackupArchive> {
+    let todos = {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM todos ORDER BY created_at",
+            TODO_COLUMNS
+        ))?;
+        let rows = stmt.query_map([], row_to_todo)?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()?
+    };
+
+    let research_results = {
+        let mut stmt = conn.prepare(
+            "SELECT id, todo_id, source, content, raw_html, started_at, completed_at, duration_seconds,
+                converter_version
+             FROM research_results ORDER BY completed_at",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(BackupResearchResult {
+                result: ResearchResult {
+                    id: row.get(0)?,
+                    todo_id: row.get(1)?,
+                    source: row.get(2)?,
+                    content: row.get(3)?,
+                    raw_html: row.get(4)?,
+                    started_at: row.get(5)?,
+                    completed_at: row.get(6)?,
+                    duration_seconds: row.get(7)?,
+                },
+                converter_version: row.get(8)?,
+            })
+        })?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()?
+    };
+
+    let settings = {
+        let mut stmt = conn.prepare("SELECT key, value FROM settings ORDER BY key")?;
+        let rows = stmt.query_map([], |row| {
+            Ok(Setting {
+                key: row.get(0)?,
+                value: row.get(1)?,
+            })
+        })?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()?
+    };
+
+    let sections = [
+        serde_json::to_value(&todos)?,
+        serde_json::to_value(&research_results)?,
+        serde_json::to_value(&settings)?,
+    ];
+
+    let mut counts = BTreeMap::new();
+    let mut checksums = BTreeMap::new();
+    for (name, section) in SECTIONS.iter().zip(sections.iter()) {
+        counts.insert(name.to_string(), section.as_array().map_or(0, |a| a.len()));
+        checksums.insert(name.to_string(), checksum(section)?);
+    }
+
+    Ok(BackupArchive {
+        manifest: BackupManifest {
+            format: BACKUP_FORMAT.to_string(),
+            version: BACKUP_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: Utc::now().to_rfc3339(),
+            counts,
+            checksums,
+        },
+        todos,
+        research_results,
+        settings,
+        app_conf,
+    })
+}
+
+/// Parses an archive and verifies its manifest, counts and checksums.
+pub fn parse_backup(contents: &str) -> Result<BackupArchive> {
+    let value: Value = serde_json::from_str(contents).context("Backup is not valid JSON")?;
+    let manifest: BackupManifest = serde_json::from_value(
+        value
+            .get("manifest")
+            .cloned()
+            .context("Backup has no manifest")?,
+    )
+    .context("Backup manifest is malformed")?;
+
+    if manifest.format != BACKUP_FORMAT {
+        bail!("Unknown backup format: {}", manifest.format);
+    }
+    if manifest.version > BACKUP_VERSION {
+        bail!(
+            "Backup version {} is newer than supported version {}",
+            manifest.version,
+            BACKUP_VERSION
+        );
+    }
+
+    for name in SECTIONS {
+        let section = value
+            .get(name)
+            .with_context(|| format!("Backup is missing section: {}", name))?;
+        let expected = manifest
+            .checksums
+            .get(name)
+            .with_context(|| format!("Manifest has no checksum for: {}", name))?;
+        if &checksum(section)? != expected {
+            bail!("Checksum mismatch in section: {}", name);
+        }
+
+        let count = section.as_array().map_or(0, |a| a.len());
+        if manifest.counts.get(name) != Some(&count) {
+            bail!("Record count mismatch in section: {}", name);
+        }
+    }
+
+    let archive: BackupArchive =
+        serde_json::from_value(value).context("Backup records are malformed")?;
+
+    // Ids end up in scripts and file names, so only accept the UUIDs the app generates.
+    for todo in &archive.todos {
+        if Uuid::parse_str(&todo.id).is_err() {
+            bail!("Todo id is not a UUID: {}", todo.id);
+        }
+    }
+    for BackupResearchResult { result, .. } in &archive.research_results {
+        if Uuid::parse_str(&result.id).is_err() {
+            bail!("Research result id is not a UUID: {}", result.id);
+        }
+        if Uuid::parse_str(&result.todo_id).is_err() {
+            bail!("Research result {} has an invalid todo id", result.id);
+        }
+    }
+
+    Ok(archive)
+}
+
+/// Imports an archive inside a single transaction, so a failure leaves the store untouched.
+pub fn restore_backup(
+    conn: &mut Connection,
+    archive: &BackupArchive,
+    mode: RestoreMode,
+) -> Result<RestoreReport> {
+    let tx = conn.transaction()?;
+
+    if mode == RestoreMode::Replace {
+        tx.execute("DELETE FROM research_results", [])?;
+        tx.execute("DELETE FROM todos", [])?;
+        tx.execute("DELETE FROM settings", [])?;
+    }
+
+    for todo in &archive.todos {
+        tx.execute(
+            "INSERT INTO todos (id, title, description, url, status, created_at, updated_at, tags, due_date,
+                deleted_at, recurrence, next_run_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+             ON CONFLICT(id) DO UPDATE SET title = excluded.title, description = excluded.description,
+                url = excluded.url, status = excluded.status, created_at = excluded.created_at,
+                updated_at = excluded.updated_at, tags = excluded.tags, due_date = excluded.due_date,
+                deleted_at = excluded.deleted_at, recurrence = excluded.recurrence,
+                next_run_at = excluded.next_run_at",
+            params![
+                todo.id,
+                todo.title,
+                todo.description,
+                todo.url,
+                todo.status.as_str(),
+                todo.created_at,
+                todo.updated_at,
+                tags_to_json(&todo.tags),
+                todo.due_date,
+                todo.deleted_at,
+                recurrence_to_json(todo.recurrence.as_ref()),
+                todo.next_run_at
+            ],
+        )?;
+    }
+
+    for BackupResearchResult {
+        result,
+        converter_version,
+    } in &archive.research_results
+    {
+        tx.execute(
+            "INSERT INTO research_results (id, todo_id, source, content, raw_html, started_at, completed_at,
+                duration_seconds, converter_version)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+             ON CONFLICT(id) DO UPDATE SET todo_id = excluded.todo_id, source = excluded.source,
+                content = excluded.content, raw_html = excluded.raw_html, started_at = excluded.started_at,
+                completed_at = excluded.completed_at, duration_seconds = excluded.duration_seconds,
+                converter_version = excluded.converter_version",
+            params![
+                result.id,
+                result.todo_id,
+                result.source,
+                result.content,
+                result.raw_html,
+                result.started_at,
+                result.completed_at,
+                result.duration_seconds,
+                converter_version
+            ],
+        )
+        .with_context(|| format!("Failed to restore research result {}", result.id))?;
+    }
+
+    for setting in &archive.settings {
+        tx.execute(
+            "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+            params![setting.key, setting.value],
+        )?;
+    }
+
+    tx.commit()?;
+
+    Ok(RestoreReport {
+        mode,
+        todos: archive.todos.len(),
+        research_results: archive.research_results.len(),
+        settings: archive.settings.len(),
+        app_conf: archive.app_conf.clone(),
+    })
+}
+
+/// Writes a backup of the open database to `path`.
+pub fn backup_to_file<P: AsRef<Path>>(path: P, app_conf: Option<Value>) -> Result<BackupManifest> {
+    let path = path.as_ref();
+    let archive = {
+        let conn = db::reader()?;
+        create_backup(&conn, app_conf)?
+    };
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(&archive)?)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+
+    info!("[backup] Wrote {}", path.display());
+    Ok(archive.manifest)
+}
+
+/// Validates the archive at `path` and restores it into the open database.
+pub fn restore_from_file<P: AsRef<Path>>(path: P, mode: RestoreMode) -> Result<RestoreReport> {
+    let path = path.as_ref();
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    // Anything wrong with the file itself is a validation failure, not a storage one.
+    let archive = parse_backup(&contents).map_err(|e| AppError::Validation(format!("{:#}", e)))?;
+
+    let mut conn = db::writer();
+    let report = restore_backup(&mut conn, &archive, mode)?;
+
+    info!("[backup] Restored {} ({:?})", path.display(), mode);
+    Ok(report)
+}
+
+fn checksum(section: &Value) -> Result<String> {
+    let bytes = serde_json::to_vec(section)?;
+    let digest = Sha256::digest(&bytes);
+    Ok(digest.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute("PRAGMA foreign_keys = ON", []).unwrap();
+        conn.execute_batch(include_str!("../migrations/001_init.sql"))
+            .unwrap();
+        conn
+    }
+
+    const TODO_ID: &str = "0b6f2a6e-3f1d-4c55-9a57-2f0d8c1e4a01";
+    const RESULT_ID: &str = "5d0c7e2b-8a44-4b1e-b3a9-6c2f1e9d7b02";
+
+    fn seed(conn: &Connection) {
+        conn.execute(
+            "INSERT INTO todos (id, title, status, created_at, updated_at)
+             VALUES (?1, 'First', 'review', '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z')",
+            [TODO_ID],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO research_results (id, todo_id, source, content, converter_version)
+             VALUES (?1, ?2, 'chatgpt', '# Report', 3)",
+            [RESULT_ID, TODO_ID],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO settings (key, value) VALUES ('k', 'v')", [])
+            .unwrap();
+    }
+
+    #[test]
+    fn test_backup_round_trip_replace() {
+        let source = open_db();
+        seed(&source);
+        let archive = create_backup(&source, None).unwrap();
+        let json = serde_json::to_string_pretty(&archive).unwrap();
+
+        let parsed = parse_backup(&json).unwrap();
+        let mut target = open_db();
+        target
+            .execute(
+                "INSERT INTO todos (id, title) VALUES ('stale', 'Stale')",
+                [],
+            )
+            .unwrap();
+
+        let report = restore_backup(&mut target, &parsed, RestoreMode::Replace).unwrap();
+        assert_eq!(report.todos, 1);
+        assert_eq!(report.research_results, 1);
+
+        let titles: Vec<String> = target
+            .prepare("SELECT title FROM todos")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(titles, vec!["First".to_string()]);
+
+        let version: Option<i64> = target
+            .query_row(
+                "SELECT converter_version FROM research_results WHERE id = ?1",
+                [RESULT_ID],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(version, Some(3));
+    }
+
+    #[test]
+    fn test_merge_keeps_existing_research() {
+        let source = open_db();
+        seed(&source);
+        let archive = create_backup(&source, None).unwrap();
+
+        // Merging into the same store must not cascade-delete research results.
+        let mut target = open_db();
+        seed(&target);
+        restore_backup(&mut target, &archive, RestoreMode::Merge).unwrap();
+
+        let count: i64 = target
+            .query_row("SELECT COUNT(*) FROM research_results", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_tampered_backup_is_rejected() {
+        let source = open_db();
+        seed(&source);
+        let archive = create_backup(&source, None).unwrap();
+        let json = serde_json::to_string(&archive)
+            .unwrap()
+            .replace("First", "Tampered");
+
+        let err = parse_backup(&json).unwrap_err();
+        assert!(err.to_string().contains("Checksum mismatch"));
+    }
+
+    #[test]
+    fn test_non_uuid_ids_are_rejected() {
+        let source = open_db();
+        seed(&source);
+        let mut archive = create_backup(&source, None).unwrap();
+        archive.todos[0].id = "x'); alert(1); ('".to_string();
+        let todos = serde_json::to_value(&archive.todos).unwrap();
+        archive
+            .manifest
+            .checksums
+            .insert("todos".to_string(), checksum(&todos).unwrap());
+        let json = serde_json::to_string(&archive).unwrap();
+
+        let err = parse_backup(&json).unwrap_err();
+        assert!(err.to_string().contains("not a UUID"));
+    }
+}
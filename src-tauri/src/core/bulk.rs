@@ -0,0 +1,301 @@
+use log::info;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::core::{
+    error::AppError,
+    repository::{SqliteTodoRepository, TodoRepository},
+    research,
+    todo::{Todo, TodoStatus, UpdateTodoInput},
+};
+
+/// An operation applied to every selected todo.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BulkAction {
+    SetStatus {
+        status: TodoStatus,
+    },
+    AddTags {
+        tags: Vec<String>,
+    },
+    RemoveTags {
+        tags: Vec<String>,
+    },
+    Archive,
+    /// Moves the todos to the trash.
+    Delete,
+    /// Marks the todos as researching; the caller queues them for the webview runner.
+    StartResearch,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkItemResult {
+    pub id: String,
+    pub success: bool,
+    /// The todo after the change; `None` on failure or when it was deleted.
+    pub todo: Option<Todo>,
+    pub error: Option<AppError>,
+    /// Status before the change, so the caller can stop runs that no longer apply.
+    #[serde(skip)]
+    pub previous_status: Option<TodoStatus>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkReport {
+    pub results: Vec<BulkItemResult>,
+    pub succeeded: usize,
+    pub failed: usize,
+}
+
+impl BulkReport {
+    pub fn succeeded_ids(&self) -> Vec<String> {
+        self.results
+            .iter()
+            .filter(|r| r.success)
+            .map(|r| r.id.clone())
+            .collect()
+    }
+
+    /// Todos that were researching before the change and no longer are, either because
+    /// their status changed or because they were deleted.
+    pub fn stopped_research_ids(&self) -> Vec<String> {
+        self.results
+            .iter()
+            .filter(|r| r.success && r.previous_status == Some(TodoStatus::Researching))
+            .filter(|r| !matches!(&r.todo, Some(t) if t.status == TodoStatus::Researching))
+            .map(|r| r.id.clone())
+            .collect()
+    }
+}
+
+/// Applies `action` to every id in one transaction. Per-id problems such as a missing
+/// todo or a disallowed status change are reported and skipped; a storage failure rolls
+/// back the whole batch.
+pub fn apply(
+    conn: &mut Connection,
+    ids: &[String],
+    action: &BulkAction,
+) -> Result<BulkReport, AppError> {
+    let tx = conn.transaction()?;
+    let repo = SqliteTodoRepository::new(&tx);
+
+    let mut results = Vec::with_capacity(ids.len());
+    for id in ids {
+        let result = match apply_one(&repo, id, action) {
+            Ok((previous_status, todo)) => BulkItemResult {
+                id: id.clone(),
+                success: true,
+                todo,
+                error: None,
+                previous_status: Some(previous_status),
+            },
+            Err(e @ AppError::Storage(_)) => return Err(e),
+            Err(e) => BulkItemResult {
+                id: id.clone(),
+                success: false,
+                todo: None,
+                error: Some(e),
+                previous_status: None,
+            },
+        };
+        results.push(result);
+    }
+
+    tx.commit()?;
+
+    let succeeded = results.iter().filter(|r| r.success).count();
+    info!(
+        "[bulk] {:?}: {} succeeded, {} failed",
+        action,
+        succeeded,
+        results.len() - succeeded
+    );
+
+    Ok(BulkReport {
+        failed: results.len() - succeeded,
+        succeeded,
+        results,
+    })
+}
+
+/// Applies `action` to one todo and returns its previous status with the updated todo.
+fn apply_one(
+    repo: &dyn TodoRepository,
+    id: &str,
+    action: &BulkAction,
+) -> Result<(TodoStatus, Option<Todo>), AppError> {
+    let todo = repo
+        .get(id)?
+        .ok_or_else(|| AppError::NotFound(format!("Todo not found: {}", id)))?;
+    let previous_status = todo.status.clone();
+
+    let input = match action {
+        BulkAction::SetStatus {
+            status: TodoStatus::Researching,
+        } => {
+            return Err(AppError::InvalidTransition(
+                "Use start_research to research todos".to_string(),
+            ))
+        }
+        BulkAction::SetStatus { status } => UpdateTodoInput {
+            status: Some(status.clone()),
+            ..Default::default()
+        },
+        BulkAction::AddTags { tags } => {
+            let mut merged = todo.tags;
+            for tag in tags {
+                if !merged.contains(tag) {
+                    merged.push(tag.clone());
+                }
+            }
+            UpdateTodoInput {
+                tags: Some(merged),
+                ..Default::default()
+            }
+        }
+        BulkAction::RemoveTags { tags } => UpdateTodoInput {
+            tags: Some(
+                todo.tags
+                    .into_iter()
+                    .filter(|t| !tags.contains(t))
+                    .collect(),
+            ),
+            ..Default::default()
+        },
+        BulkAction::Archive => UpdateTodoInput {
+            status: Some(TodoStatus::Archived),
+            ..Default::default()
+        },
+        BulkAction::Delete => {
+            repo.delete(id)?;
+            return Ok((previous_status, None));
+        }
+        BulkAction::StartResearch => {
+            research::check_can_start(&todo)?;
+            UpdateTodoInput {
+                status: Some(TodoStatus::Researching),
+                ..Default::default()
+            }
+        }
+    };
+
+    Ok((previous_status, repo.update(id, input)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::todo::{insert_todo, CreateTodoInput};
+
+    fn open_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(include_str!("../migrations/001_init.sql"))
+            .unwrap();
+        conn
+    }
+
+    fn seed(conn: &Connection, title: &str, status: TodoStatus, tags: &[&str]) -> String {
+        insert_todo(
+            conn,
+            CreateTodoInput {
+                title: title.to_string(),
+                status: Some(status),
+                tags: tags.iter().map(|t| t.to_string()).collect(),
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .id
+    }
+
+    #[test]
+    fn test_tags_and_per_id_report() {
+        let mut conn = open_db();
+        let a = seed(&conn, "A", TodoStatus::Pending, &["ev"]);
+        let b = seed(&conn, "B", TodoStatus::Done, &["ev", "old"]);
+        let ids = vec![a, "missing".to_string(), b.clone()];
+
+        let action = BulkAction::AddTags {
+            tags: vec!["q3".to_string(), "ev".to_string()],
+        };
+        let report = apply(&mut conn, &ids, &action).unwrap();
+        assert_eq!((report.succeeded, report.failed), (2, 1));
+        assert_eq!(report.results[1].error.as_ref().unwrap().code(), "NotFound");
+        assert_eq!(
+            report.results[0].todo.as_ref().unwrap().tags,
+            vec!["ev".to_string(), "q3".to_string()]
+        );
+
+        let action = BulkAction::RemoveTags {
+            tags: vec!["old".to_string(), "ev".to_string()],
+        };
+        let report = apply(&mut conn, std::slice::from_ref(&b), &action).unwrap();
+        assert_eq!(
+            report.results[0].todo.as_ref().unwrap().tags,
+            vec!["q3".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_start_research_checks_transitions() {
+        let mut conn = open_db();
+        let pending = seed(&conn, "Pending", TodoStatus::Pending, &[]);
+        let running = seed(&conn, "Running", TodoStatus::Researching, &[]);
+        let archived = seed(&conn, "Archived", TodoStatus::Archived, &[]);
+
+        let ids = vec![pending.clone(), running, archived];
+        let report = apply(&mut conn, &ids, &BulkAction::StartResearch).unwrap();
+        assert_eq!(report.succeeded_ids(), vec![pending]);
+        assert!(report.results[1..]
+            .iter()
+            .all(|r| r.error.as_ref().unwrap().code() == "InvalidTransition"));
+    }
+
+    #[test]
+    fn test_archive_and_delete() {
+        let mut conn = open_db();
+        let a = seed(&conn, "A", TodoStatus::Done, &[]);
+        let b = seed(&conn, "B", TodoStatus::Review, &[]);
+
+        let report = apply(&mut conn, std::slice::from_ref(&a), &BulkAction::Archive).unwrap();
+        assert_eq!(
+            report.results[0].todo.as_ref().unwrap().status,
+            TodoStatus::Archived
+        );
+
+        let report = apply(&mut conn, &[a, b], &BulkAction::Delete).unwrap();
+        assert_eq!(report.succeeded, 2);
+        let trashed: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM todos WHERE deleted_at IS NOT NULL",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(trashed, 2);
+    }
+
+    #[test]
+    fn test_stopped_research_ids() {
+        let mut conn = open_db();
+        let a = seed(&conn, "A", TodoStatus::Researching, &[]);
+        let b = seed(&conn, "B", TodoStatus::Researching, &[]);
+        let c = seed(&conn, "C", TodoStatus::Pending, &[]);
+
+        let action = BulkAction::AddTags {
+            tags: vec!["q3".to_string()],
+        };
+        let report = apply(&mut conn, &[a.clone(), b.clone()], &action).unwrap();
+        assert!(report.stopped_research_ids().is_empty());
+
+        let action = BulkAction::SetStatus {
+            status: TodoStatus::Done,
+        };
+        let report = apply(&mut conn, &[a.clone(), c], &action).unwrap();
+        assert_eq!(report.stopped_research_ids(), vec![a]);
+
+        let report = apply(&mut conn, std::slice::from_ref(&b), &BulkAction::Delete).unwrap();
+        assert_eq!(report.stopped_research_ids(), vec![b]);
+    }
+}
@@ -1,248 +1,613 @@
-use tauri::{command, AppHandle, LogicalPosition, Manager, PhysicalSize};
+use log::warn;
+use tauri::{command, AppHandle, LogicalPosition, Manager, PhysicalSize, Webview, Window};
+use tauri_plugin_dialog::DialogExt;
 
 use crate::core::{
+    backup::{self, BackupManifest, RestoreMode, RestoreReport},
+    bulk::{self, BulkAction, BulkReport},
     conf::AppConf,
-    constant::{ASK_HEIGHT, TITLEBAR_HEIGHT},
+    constant::{ASK_HEIGHT, TITLEBAR_HEIGHT, WINDOW_SETTINGS, WINDOW_TODO},
+    db,
+    error::AppError,
+    formats::{self, ExportFormat},
+    import::{self, ImportFormat, ImportMapping, ImportPreview, ImportResult},
+    markdown,
+    notify::NotificationPrefs,
+    pdf,
+    recurrence::{self, Recurrence},
+    reminders::{self, ReminderSettings},
+    research,
+    rules::{self, RuleChange, StatusRule},
+    scripts::{self, UserScript},
+    selectors::{self, SelectorPack},
+    selftest::{self, SelftestReport},
+    settings,
+    snapshot::{self, SnapshotInfo},
+    template::Template,
     todo::{
-        self, CreateTodoInput, StatusCounts, Todo, TodoStatus, TodoWithResearch, UpdateTodoInput,
+        self, CreateTodoInput, ResearchResult, StatusCounts, Todo, TodoStatus, TodoWithResearch,
+        UpdateTodoInput,
     },
+    window,
 };
 
-#[command]
-pub fn view_reload(app: AppHandle) {
+/// The core window is built asynchronously during setup, so commands can arrive before it
+/// exists.
+fn core_window(app: &AppHandle) -> Result<Window, AppError> {
     app.get_window("core")
-        .unwrap()
-        .get_webview("main")
-        .unwrap()
-        .eval("window.location.reload()")
-        .unwrap();
+        .ok_or_else(|| AppError::Webview("Core window has not been created yet".to_string()))
 }
 
-#[command]
-pub fn view_url(app: AppHandle) -> tauri::Url {
-    app.get_window("core")
-        .unwrap()
-        .get_webview("main")
-        .unwrap()
-        .url()
-        .unwrap()
+fn core_webview(app: &AppHandle, label: &str) -> Result<Webview, AppError> {
+    core_window(app)?
+        .get_webview(label)
+        .ok_or_else(|| AppError::Webview(format!("Webview '{}' has not been created yet", label)))
 }
 
 #[command]
-pub fn view_go_forward(app: AppHandle) {
-    app.get_window("core")
-        .unwrap()
-        .get_webview("main")
-        .unwrap()
-        .eval("window.history.forward()")
-        .unwrap();
+pub fn view_reload(app: AppHandle) -> Result<(), AppError> {
+    Ok(core_webview(&app, "main")?.eval("window.location.reload()")?)
 }
 
 #[command]
-pub fn view_go_back(app: AppHandle) {
-    app.get_window("core")
-        .unwrap()
-        .get_webview("main")
-        .unwrap()
-        .eval("window.history.back()")
-        .unwrap();
+pub fn view_url(app: AppHandle) -> Result<tauri::Url, AppError> {
+    Ok(core_webview(&app, "main")?.url()?)
 }
 
 #[command]
-pub fn window_pin(app: AppHandle, pin: bool) {
-    let conf = AppConf::load(&app).unwrap();
-    conf.amend(serde_json::json!({"stay_on_top": pin}))
-        .unwrap()
-        .save(&app)
-        .unwrap();
+pub fn view_go_forward(app: AppHandle) -> Result<(), AppError> {
+    Ok(core_webview(&app, "main")?.eval("window.history.forward()")?)
+}
 
-    app.get_window("core")
-        .unwrap()
-        .set_always_on_top(pin)
-        .unwrap();
+#[command]
+pub fn view_go_back(app: AppHandle) -> Result<(), AppError> {
+    Ok(core_webview(&app, "main")?.eval("window.history.back()")?)
 }
 
 #[command]
-pub fn ask_sync(app: AppHandle, message: String) {
-    app.get_window("core")
-        .unwrap()
-        .get_webview("main")
-        .unwrap()
-        .eval(&format!("ChatAsk.sync({})", message))
-        .unwrap();
+pub async fn window_pin(app: AppHandle, pin: bool) -> Result<(), AppError> {
+    let handle = app.clone();
+    blocking(move || settings::update(&handle, serde_json::json!({"stay_on_top": pin}))).await?;
+
+    // Without a window the saved setting is applied when setup builds it.
+    if let Some(win) = app.get_window("core") {
+        win.set_always_on_top(pin)?;
+    }
+    Ok(())
 }
 
 #[command]
-pub fn ask_send(app: AppHandle) {
-    let win = app.get_window("core").unwrap();
+pub fn ask_sync(app: AppHandle, message: String) -> Result<(), AppError> {
+    Ok(core_webview(&app, "main")?.eval(&format!("ChatAsk.sync({})", message))?)
+}
 
-    win.get_webview("main")
-        .unwrap()
-        .eval(
-            r#"
+#[command]
+pub fn ask_send(app: AppHandle) -> Result<(), AppError> {
+    Ok(core_webview(&app, "main")?.eval(
+        r#"
         ChatAsk.submit();
         setTimeout(() => {
             __TAURI__.webview.Webview.getByLabel('ask')?.setFocus();
         }, 500);
         "#,
-        )
-        .unwrap();
+    )?)
+}
+
+#[command]
+pub async fn set_theme(app: AppHandle, theme: String) -> Result<(), AppError> {
+    let (handle, value) = (app.clone(), theme.clone());
+    blocking(move || settings::update(&handle, serde_json::json!({ "theme": value }))).await?;
+    window::apply_theme(&app, &theme);
+    Ok(())
+}
+
+#[command]
+pub async fn get_app_conf() -> Result<AppConf, AppError> {
+    blocking(settings::load).await
 }
 
 #[command]
-pub fn set_theme(app: AppHandle, theme: String) {
-    let conf = AppConf::load(&app).unwrap();
-    conf.amend(serde_json::json!({"theme": theme}))
-        .unwrap()
-        .save(&app)
-        .unwrap();
+pub async fn cmd_update_settings(
+    app: AppHandle,
+    changes: serde_json::Value,
+) -> Result<AppConf, AppError> {
+    blocking(move || settings::update(&app, changes)).await
+}
 
-    app.restart();
+/// Runs blocking database work on the blocking thread pool, so long writes never stall
+/// the main thread or other IPC calls.
+async fn blocking<T, F>(f: F) -> Result<T, AppError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, AppError> + Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(f)
+        .await
+        .map_err(|e| AppError::Storage(e.to_string()))?
 }
 
 #[command]
-pub fn get_app_conf(app: AppHandle) -> AppConf {
-    AppConf::load(&app).unwrap()
+pub async fn cmd_create_todo(input: CreateTodoInput) -> Result<Todo, AppError> {
+    blocking(move || Ok(todo::create_todo(input)?)).await
 }
 
 #[command]
-pub fn cmd_create_todo(input: CreateTodoInput) -> Result<Todo, String> {
-    todo::create_todo(input).map_err(|e| e.to_string())
+pub async fn cmd_get_todos(status: Option<String>) -> Result<Vec<Todo>, AppError> {
+    blocking(move || Ok(todo::get_todos(status.as_deref())?)).await
 }
 
 #[command]
-pub fn cmd_get_todos(status: Option<String>) -> Result<Vec<Todo>, String> {
-    todo::get_todos(status.as_deref()).map_err(|e| e.to_string())
+pub async fn cmd_get_todo_detail(id: String) -> Result<Option<TodoWithResearch>, AppError> {
+    blocking(move || Ok(todo::get_todo_with_research(&id)?)).await
 }
 
 #[command]
-pub fn cmd_get_todo_detail(id: String) -> Result<Option<TodoWithResearch>, String> {
-    todo::get_todo_with_research(&id).map_err(|e| e.to_string())
+pub async fn cmd_update_todo(id: String, input: UpdateTodoInput) -> Result<Option<Todo>, AppError> {
+    blocking(move || Ok(todo::update_todo(&id, input)?)).await
 }
 
 #[command]
-pub fn cmd_update_todo(id: String, input: UpdateTodoInput) -> Result<Option<Todo>, String> {
-    todo::update_todo(&id, input).map_err(|e| e.to_string())
+pub async fn cmd_delete_todo(id: String) -> Result<bool, AppError> {
+    blocking(move || Ok(todo::delete_todo(&id)?)).await
 }
 
 #[command]
-pub fn cmd_delete_todo(id: String) -> Result<bool, String> {
-    todo::delete_todo(&id).map_err(|e| e.to_string())
+pub async fn cmd_get_trash() -> Result<Vec<Todo>, AppError> {
+    blocking(move || Ok(todo::get_trash()?)).await
 }
 
 #[command]
-pub fn cmd_get_status_counts() -> Result<StatusCounts, String> {
-    todo::get_status_counts().map_err(|e| e.to_string())
+pub async fn cmd_restore_todo(id: String) -> Result<Todo, AppError> {
+    blocking(move || {
+        todo::restore_todo(&id)?
+            .ok_or_else(|| AppError::NotFound(format!("Todo not in trash: {}", id)))
+    })
+    .await
 }
 
 #[command]
-pub fn cmd_start_research(app: AppHandle, id: String) -> Result<Option<Todo>, String> {
-    let todo = todo::get_todo_by_id(&id).map_err(|e| e.to_string())?;
+pub async fn cmd_purge_todo(id: String) -> Result<(), AppError> {
+    blocking(move || {
+        if !todo::purge_todo(&id)? {
+            return Err(AppError::NotFound(format!("Todo not in trash: {}", id)));
+        }
+        Ok(())
+    })
+    .await
+}
 
-    if let Some(t) = todo {
-        let input = UpdateTodoInput {
-            title: None,
-            description: None,
-            url: None,
-            status: Some(TodoStatus::Researching),
+#[command]
+pub async fn cmd_empty_trash() -> Result<usize, AppError> {
+    blocking(move || Ok(todo::purge_trash(0)?)).await
+}
+
+#[command]
+pub async fn cmd_get_status_counts() -> Result<StatusCounts, AppError> {
+    blocking(move || Ok(todo::get_status_counts()?)).await
+}
+
+#[command]
+pub async fn cmd_start_research(app: AppHandle, id: String) -> Result<Option<Todo>, AppError> {
+    blocking(move || {
+        // Refuse up front instead of starting a run that silently does nothing. A todo
+        // that only gets queued is checked by the run ahead of it.
+        if !research::is_busy() {
+            selftest::run(&app)?.ensure_passed()?;
+        }
+        research::begin(&app, &id)
+    })
+    .await
+}
+
+#[command]
+pub async fn cmd_set_recurrence(
+    id: String,
+    recurrence: Option<Recurrence>,
+) -> Result<Option<Todo>, AppError> {
+    blocking(move || recurrence::set_recurrence(&id, recurrence)).await
+}
+
+/// Converts the todo's stored reports from their raw HTML again and returns them.
+#[command]
+pub async fn cmd_rerender_research(todo_id: String) -> Result<Vec<ResearchResult>, AppError> {
+    blocking(move || {
+        let conn = db::reader()?;
+        markdown::rerender_todo(&conn, db::writer, &todo_id)?;
+        Ok(todo::get_research_history(&todo_id)?)
+    })
+    .await
+}
+
+#[command]
+pub async fn cmd_get_research_history(todo_id: String) -> Result<Vec<ResearchResult>, AppError> {
+    blocking(move || Ok(todo::get_research_history(&todo_id)?)).await
+}
+
+#[command]
+pub async fn cmd_cancel_research(app: AppHandle, id: String) -> Result<Option<Todo>, AppError> {
+    blocking(move || {
+        let Some(t) = todo::get_todo_by_id(&id)? else {
+            return Ok(None);
         };
-        let updated = todo::update_todo(&id, input).map_err(|e| e.to_string())?;
-
-        // Trigger research in the main webview
-        let mut started = false;
-        if let Some(win) = app.get_window("core") {
-            if let Some(webview) = win.get_webview("main") {
-                let prompt = format!(
-                    "Please research: {}. Context: {}",
-                    t.title,
-                    t.description
-                        .unwrap_or_else(|| "No description provided".to_string())
-                );
-                // We need to escape the prompt for JS string injection
-                let escaped_prompt = prompt.replace("'", "\\'").replace("\n", "\\n");
-                let script = format!("window.DeepResearch.start('{}', '{}')", id, escaped_prompt);
-                started = webview.eval(&script).is_ok();
-            }
+
+        if t.status != TodoStatus::Researching {
+            return Err(AppError::InvalidTransition(format!(
+                "Cannot cancel research on a todo that is {}",
+                t.status.as_str()
+            )));
         }
 
-        let final_todo = if !started {
-            let rollback = UpdateTodoInput {
-                title: None,
-                description: None,
-                url: None,
-                status: Some(TodoStatus::Pending),
-            };
-            todo::update_todo(&id, rollback).unwrap_or(updated)
-        } else {
-            updated
+        let input = UpdateTodoInput {
+            status: Some(TodoStatus::Pending),
+            ..Default::default()
         };
+        let updated = todo::update_todo(&id, input)?;
+        research::cancel(&app, &id);
 
-        Ok(final_todo)
-    } else {
-        Ok(None)
-    }
+        Ok(updated)
+    })
+    .await
 }
 
 #[command]
-pub fn cmd_cancel_research(app: AppHandle, id: String) -> Result<Option<Todo>, String> {
-    let input = UpdateTodoInput {
-        title: None,
-        description: None,
-        url: None,
-        status: Some(TodoStatus::Pending),
-    };
-    let updated = todo::update_todo(&id, input).map_err(|e| e.to_string())?;
+pub async fn cmd_bulk_update(
+    app: AppHandle,
+    ids: Vec<String>,
+    action: BulkAction,
+) -> Result<BulkReport, AppError> {
+    blocking(move || {
+        if action == BulkAction::StartResearch && !research::is_busy() {
+            selftest::run(&app)?.ensure_passed()?;
+        }
+        let report = bulk::apply(&mut db::writer(), &ids, &action)?;
 
-    if let Some(win) = app.get_window("core") {
-        if let Some(webview) = win.get_webview("main") {
-            let _ = webview.eval("window.DeepResearch.cancel()");
+        if let BulkAction::StartResearch = action {
+            research::enqueue(&app, report.succeeded_ids());
+        }
+        for id in report.stopped_research_ids() {
+            research::cancel(&app, &id);
         }
-    }
 
-    Ok(updated)
+        Ok(report)
+    })
+    .await
 }
 
 #[command]
-pub fn cmd_save_research_result(
+pub async fn cmd_export_research_pdf(
+    webview: Webview,
+    id: String,
+    path: String,
+) -> Result<(), AppError> {
+    ensure_app_window(&webview)?;
+    blocking(move || {
+        let detail = todo::get_todo_with_research(&id)?
+            .ok_or_else(|| AppError::NotFound(format!("Todo not found: {}", id)))?;
+        let research = detail
+            .research
+            .ok_or_else(|| AppError::NotFound(format!("No research result for todo: {}", id)))?;
+
+        Ok(pdf::export_report(&detail.todo, &research, path)?)
+    })
+    .await
+}
+
+#[command]
+pub async fn cmd_backup(webview: Webview, path: String) -> Result<BackupManifest, AppError> {
+    ensure_settings_window(&webview)?;
+    blocking(move || {
+        let app_conf = settings::load()
+            .ok()
+            .and_then(|conf| serde_json::to_value(conf).ok());
+        Ok(backup::backup_to_file(path, app_conf)?)
+    })
+    .await
+}
+
+#[command]
+pub async fn cmd_restore(
+    app: AppHandle,
+    webview: Webview,
+    path: String,
+    mode: Option<RestoreMode>,
+) -> Result<RestoreReport, AppError> {
+    ensure_settings_window(&webview)?;
+    blocking(move || {
+        let report = backup::restore_from_file(path, mode.unwrap_or_default())?;
+
+        if let Some(app_conf) = report.app_conf.clone() {
+            settings::update(&app, app_conf)?;
+        }
+
+        Ok(report)
+    })
+    .await
+}
+
+#[command]
+pub async fn cmd_import_preview(
+    webview: Webview,
+    path: String,
+    format: Option<ImportFormat>,
+    mapping: Option<ImportMapping>,
+) -> Result<ImportPreview, AppError> {
+    ensure_app_window(&webview)?;
+    blocking(move || {
+        Ok(import::preview_file(
+            path,
+            format,
+            &mapping.unwrap_or_default(),
+        )?)
+    })
+    .await
+}
+
+#[command]
+pub async fn cmd_import_todos(
+    webview: Webview,
+    path: String,
+    format: Option<ImportFormat>,
+    mapping: Option<ImportMapping>,
+    skip_invalid: Option<bool>,
+) -> Result<ImportResult, AppError> {
+    ensure_app_window(&webview)?;
+    blocking(move || {
+        Ok(import::import_file(
+            path,
+            format,
+            &mapping.unwrap_or_default(),
+            skip_invalid.unwrap_or(false),
+        )?)
+    })
+    .await
+}
+
+#[command]
+pub async fn cmd_export_todos(
+    webview: Webview,
+    path: String,
+    format: ExportFormat,
+    status: Option<String>,
+) -> Result<usize, AppError> {
+    ensure_app_window(&webview)?;
+    blocking(move || Ok(formats::export_file(path, format, status.as_deref())?)).await
+}
+
+#[command]
+pub async fn cmd_set_notification_prefs(
+    app: AppHandle,
+    prefs: NotificationPrefs,
+) -> Result<NotificationPrefs, AppError> {
+    prefs.validate()?;
+    blocking(move || {
+        Ok(settings::update(&app, serde_json::json!({ "notifications": prefs }))?.notifications)
+    })
+    .await
+}
+
+#[command]
+pub async fn cmd_get_reminders(todo_id: String) -> Result<ReminderSettings, AppError> {
+    blocking(move || {
+        let conn = db::reader()?;
+        reminders::get_settings(&conn, &todo_id)
+    })
+    .await
+}
+
+#[command]
+pub async fn cmd_set_reminder_offsets(
+    todo_id: String,
+    offsets: Option<Vec<u32>>,
+) -> Result<ReminderSettings, AppError> {
+    blocking(move || reminders::set_offsets(&db::writer(), &todo_id, offsets)).await
+}
+
+#[command]
+pub async fn cmd_snooze_reminder(
     todo_id: String,
+    minutes: u32,
+) -> Result<ReminderSettings, AppError> {
+    blocking(move || reminders::snooze(&db::writer(), &todo_id, minutes, chrono::Utc::now())).await
+}
+
+/// The scripts directory, without upgrading anything; that happens once at startup.
+fn script_dir(app: &AppHandle) -> Result<Template, AppError> {
+    Ok(Template {
+        dir: AppConf::get_scripts_path(app)?,
+    })
+}
+
+/// Commands that change what gets injected into the ChatGPT page, or that back up and
+/// restore the whole store, are only accepted from the Settings window. The capability
+/// grants IPC to the remote page too, and it must not be able to install scripts or
+/// selectors for the next launch.
+fn ensure_settings_window(webview: &Webview) -> Result<(), AppError> {
+    if webview.label() != WINDOW_SETTINGS {
+        return Err(AppError::Validation(format!(
+            "Only the Settings window may do this, not '{}'",
+            webview.label()
+        )));
+    }
+    Ok(())
+}
+
+/// Commands that read or write a caller-chosen path are only accepted from the app's own
+/// windows, so the remote ChatGPT page cannot read or overwrite files.
+fn ensure_app_window(webview: &Webview) -> Result<(), AppError> {
+    if ![WINDOW_SETTINGS, WINDOW_TODO].contains(&webview.label()) {
+        return Err(AppError::Validation(format!(
+            "Only the Todo and Settings windows may do this, not '{}'",
+            webview.label()
+        )));
+    }
+    Ok(())
+}
+
+/// Script changes apply to the main webview the next time the app starts.
+#[command]
+pub async fn cmd_get_scripts(app: AppHandle) -> Result<Vec<UserScript>, AppError> {
+    let template = script_dir(&app)?;
+    blocking(move || {
+        let conn = db::reader()?;
+        scripts::list(&conn, &template)
+    })
+    .await
+}
+
+#[command]
+pub async fn cmd_set_script_enabled(
+    app: AppHandle,
+    webview: Webview,
+    file: String,
+    enabled: bool,
+) -> Result<UserScript, AppError> {
+    ensure_settings_window(&webview)?;
+    let template = script_dir(&app)?;
+    blocking(move || scripts::set_enabled(&db::writer(), &template, &file, enabled)).await
+}
+
+#[command]
+pub async fn cmd_set_script_matches(
+    app: AppHandle,
+    webview: Webview,
+    file: String,
+    matches: Option<Vec<String>>,
+) -> Result<UserScript, AppError> {
+    ensure_settings_window(&webview)?;
+    let template = script_dir(&app)?;
+    blocking(move || scripts::set_matches(&db::writer(), &template, &file, matches)).await
+}
+
+#[command]
+pub async fn cmd_add_script(
+    app: AppHandle,
+    webview: Webview,
+    file: String,
     source: String,
-    content: String,
-    raw_html: Option<String>,
-    started_at: String,
-) -> Result<todo::ResearchResult, String> {
-    todo::save_research_result(
-        &todo_id,
-        &source,
-        &content,
-        raw_html.as_deref(),
-        &started_at,
-    )
-    .map_err(|e| e.to_string())
-}
-
-#[command]
-pub fn set_view_ask(app: AppHandle, enabled: bool) {
-    let conf = AppConf::load(&app).unwrap();
-    conf.amend(serde_json::json!({"ask_mode": enabled}))
-        .unwrap()
-        .save(&app)
-        .unwrap();
-
-    let core_window = app.get_window("core").unwrap();
+) -> Result<UserScript, AppError> {
+    ensure_settings_window(&webview)?;
+    let template = script_dir(&app)?;
+    blocking(move || scripts::add(&db::writer(), &template, &file, &source)).await
+}
+
+#[command]
+pub async fn cmd_remove_script(
+    app: AppHandle,
+    webview: Webview,
+    file: String,
+) -> Result<(), AppError> {
+    ensure_settings_window(&webview)?;
+    let template = script_dir(&app)?;
+    blocking(move || scripts::remove(&db::writer(), &template, &file)).await
+}
+
+/// The active selector pack. `deep_research.js` asks for it on every page load, so a
+/// pack loaded since the app started survives reloads.
+#[command]
+pub async fn cmd_get_selector_pack(app: AppHandle) -> Result<SelectorPack, AppError> {
+    let dir = AppConf::get_scripts_path(&app)?;
+    blocking(move || Ok(selectors::read(&dir).unwrap_or_else(|_| SelectorPack::bundled()))).await
+}
+
+/// Lets the user pick a selector pack file, validates it, makes it the active one and
+/// applies it to the running automation. Returns `None` when the dialog is cancelled.
+/// The path comes from the app's own dialog, so no webview can make it read other files.
+#[command]
+pub async fn cmd_load_selector_pack(
+    app: AppHandle,
+    webview: Webview,
+) -> Result<Option<SelectorPack>, AppError> {
+    ensure_settings_window(&webview)?;
+    let dir = AppConf::get_scripts_path(&app)?;
+    let handle = app.clone();
+    let pack = blocking(move || {
+        let Some(file) = handle
+            .dialog()
+            .file()
+            .set_title("加载选择器包")
+            .add_filter("Selector pack", &["json"])
+            .blocking_pick_file()
+        else {
+            return Ok(None);
+        };
+        let path = file
+            .into_path()
+            .map_err(|e| AppError::Validation(format!("Unsupported file: {}", e)))?;
+        selectors::load_file(&dir, &path).map(Some)
+    })
+    .await?;
+
+    if let Some(pack) = &pack {
+        if let Err(e) = selectors::apply(&app, pack) {
+            warn!("[selectors] Applies after the next reload: {}", e);
+        }
+    }
+    Ok(pack)
+}
+
+/// Checks which parts of the ChatGPT page the automation can find.
+#[command]
+pub async fn cmd_automation_selftest(app: AppHandle) -> Result<SelftestReport, AppError> {
+    blocking(move || selftest::run(&app)).await
+}
+
+#[command]
+pub async fn cmd_get_status_rules() -> Result<Vec<StatusRule>, AppError> {
+    blocking(move || Ok(rules::load_rules()?)).await
+}
+
+#[command]
+pub async fn cmd_set_status_rules(rules: Vec<StatusRule>) -> Result<(), AppError> {
+    blocking(move || rules::save_rules(&rules)).await
+}
+
+#[command]
+pub async fn cmd_run_status_rules() -> Result<Vec<RuleChange>, AppError> {
+    blocking(move || Ok(rules::run_now()?)).await
+}
+
+#[command]
+pub async fn cmd_list_snapshots(app: AppHandle) -> Result<Vec<SnapshotInfo>, AppError> {
+    blocking(move || {
+        let dir = snapshot::get_snapshots_path(&app)?;
+        Ok(snapshot::list_snapshots(dir)?)
+    })
+    .await
+}
+
+#[command]
+pub async fn cmd_create_snapshot(app: AppHandle) -> Result<SnapshotInfo, AppError> {
+    blocking(move || Ok(snapshot::snapshot_now(&app)?)).await
+}
+
+#[command]
+pub async fn cmd_restore_snapshot(app: AppHandle, name: String) -> Result<(), AppError> {
+    blocking(move || Ok(snapshot::restore_now(&app, &name)?)).await
+}
+
+#[command]
+pub async fn set_view_ask(app: AppHandle, enabled: bool) -> Result<(), AppError> {
+    let handle = app.clone();
+    blocking(move || settings::update(&handle, serde_json::json!({"ask_mode": enabled}))).await?;
+
+    // Setup lays the views out from the saved setting once the window exists.
+    let Some(core_window) = app.get_window("core") else {
+        return Ok(());
+    };
     let ask_mode_height = if enabled { ASK_HEIGHT } else { 0.0 };
-    let scale_factor = core_window.scale_factor().unwrap();
+    let scale_factor = core_window.scale_factor()?;
     let titlebar_height = (scale_factor * TITLEBAR_HEIGHT).round() as u32;
-    let win_size = core_window.inner_size().unwrap();
+    let win_size = core_window.inner_size()?;
     let ask_height = (scale_factor * ask_mode_height).round() as u32;
 
-    let main_view = core_window.get_webview("main").unwrap();
-    let titlebar_view = core_window.get_webview("titlebar").unwrap();
-    let ask_view = core_window.get_webview("ask").unwrap();
+    let main_view = core_webview(&app, "main")?;
+    let titlebar_view = core_webview(&app, "titlebar")?;
+    let ask_view = core_webview(&app, "ask")?;
 
     if enabled {
-        ask_view.set_focus().unwrap();
+        ask_view.set_focus()?;
     } else {
-        main_view.set_focus().unwrap();
+        main_view.set_focus()?;
     }
 
     let set_view_properties =
@@ -307,4 +672,6 @@ pub fn set_view_ask(app: AppHandle, enabled: bool) {
             PhysicalSize::new(win_size.width, ask_height),
         );
     }
+
+    Ok(())
 }
@@ -2,7 +2,11 @@ pub static TITLEBAR_HEIGHT: f64 = 28.0;
 pub static ASK_HEIGHT: f64 = 120.0;
 
 pub static WINDOW_SETTINGS: &str = "settings";
-pub static WINDOW_TODO: &str = "todo-window";
+pub static WINDOW_TODO: &str = "todo";
+
+/// Identifier used before AI Todo had its own; its directories are shared with the
+/// ChatGPT app.
+pub static LEGACY_IDENTIFIER: &str = "com.nofwl.chatgpt";
 
 pub static INIT_SCRIPT: &str = r#"
 window.addEventListener('DOMContentLoaded', function() {
@@ -1,69 +1,147 @@
 use std::path::PathBuf;
-use std::sync::Mutex;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
 
 use once_cell::sync::OnceCell;
-use rusqlite::{Connection, Result as SqliteResult};
+use r2d2::{Pool, PooledConnection};
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::{ffi, Connection, Result as SqliteResult};
 use tauri::{AppHandle, Manager};
 
-static DB_INSTANCE: OnceCell<Mutex<Connection>> = OnceCell::new();
+use crate::core::error::AppError;
+
+static DB_INSTANCE: OnceCell<Database> = OnceCell::new();
+
+/// Number of pooled read-only connections.
+const READER_POOL_SIZE: u32 = 4;
+
+/// Per-connection pragmas. WAL lets readers run while the writer holds a transaction.
+const CONNECTION_PRAGMAS: &str =
+    "PRAGMA foreign_keys = ON; PRAGMA busy_timeout = 5000; PRAGMA synchronous = NORMAL;";
+
+pub type ReadConnection = PooledConnection<SqliteConnectionManager>;
+
+/// A single writer connection plus a pool of readers over the same WAL database.
+pub struct Database {
+    writer: Mutex<Connection>,
+    readers: Pool<SqliteConnectionManager>,
+}
 
 /// Get the database file path
-pub fn get_db_path(app: &AppHandle) -> Result<PathBuf, Box<dyn std::error::Error>> {
-    let data_dir = app.path().app_data_dir()?;
+pub fn get_db_path(app: &AppHandle) -> Result<PathBuf, AppError> {
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| AppError::Storage(e.to_string()))?;
 
     std::fs::create_dir_all(&data_dir)?;
     Ok(data_dir.join("aitodo.db"))
 }
 
-/// Initialize the database connection and create tables
-pub fn init_db(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+/// Initialize the database connections and create tables
+pub fn init_db(app: &AppHandle) -> Result<(), AppError> {
     let db_path = get_db_path(app)?;
     let conn = Connection::open(&db_path)?;
 
-    // Enable foreign keys
-    conn.execute("PRAGMA foreign_keys = ON", [])?;
+    // WAL is persistent, so it only needs to be switched on from one connection.
+    conn.query_row("PRAGMA journal_mode = WAL", [], |row| {
+        row.get::<_, String>(0)
+    })?;
+    conn.execute_batch(CONNECTION_PRAGMAS)?;
+
+    upgrade_schema(&conn)?;
+
+    let manager = SqliteConnectionManager::file(&db_path)
+        .with_flags(
+            rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY | rusqlite::OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )
+        .with_init(|c| c.execute_batch(CONNECTION_PRAGMAS));
+    let readers = Pool::builder()
+        .max_size(READER_POOL_SIZE)
+        .connection_timeout(Duration::from_secs(10))
+        .build(manager)?;
+
+    // Store the connections
+    DB_INSTANCE
+        .set(Database {
+            writer: Mutex::new(conn),
+            readers,
+        })
+        .map_err(|_| AppError::Storage("Database already initialized".to_string()))?;
 
+    Ok(())
+}
+
+/// Creates missing tables and adds columns introduced since the database was created.
+/// Safe to run repeatedly, and needed whenever an older database file is swapped in.
+pub fn upgrade_schema(conn: &Connection) -> SqliteResult<()> {
     // Create tables using migration file
     let migration_sql = include_str!("../migrations/001_init.sql");
     conn.execute_batch(migration_sql)?;
-    ensure_research_results_raw_html_column(&conn)?;
-
-    // Store the connection
-    DB_INSTANCE
-        .set(Mutex::new(conn))
-        .map_err(|_| "Database already initialized")?;
-
+    ensure_column(conn, "research_results", "raw_html", "TEXT")?;
+    ensure_column(conn, "research_results", "converter_version", "INTEGER")?;
+    ensure_column(conn, "todos", "tags", "TEXT DEFAULT '[]'")?;
+    ensure_column(conn, "todos", "due_date", "TEXT")?;
+    ensure_column(conn, "todos", "deleted_at", "TEXT")?;
+    ensure_column(conn, "todos", "recurrence", "TEXT")?;
+    ensure_column(conn, "todos", "next_run_at", "TEXT")?;
     Ok(())
 }
 
-fn ensure_research_results_raw_html_column(conn: &Connection) -> SqliteResult<()> {
-    let mut stmt = conn.prepare("PRAGMA table_info(research_results)")?;
+/// Adds `column` to `table` for databases created before the column existed.
+fn ensure_column(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> SqliteResult<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
     let columns = stmt.query_map([], |row| row.get::<_, String>(1))?;
 
-    let mut has_raw_html = false;
-    for column in columns {
-        if column.as_deref() == Ok("raw_html") {
-            has_raw_html = true;
+    let mut has_column = false;
+    for name in columns {
+        if name.as_deref() == Ok(column) {
+            has_column = true;
             break;
         }
     }
 
-    if !has_raw_html {
-        conn.execute("ALTER TABLE research_results ADD COLUMN raw_html TEXT", [])?;
+    if !has_column {
+        conn.execute(
+            &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+            [],
+        )?;
     }
 
     Ok(())
 }
 
-/// Get a reference to the database connection
-pub fn get_db() -> &'static Mutex<Connection> {
+fn get_db() -> &'static Database {
     DB_INSTANCE.get().expect("Database not initialized")
 }
 
-#[allow(dead_code)]
+/// Lock the single writer connection. Writes (and read-modify-write sequences) go here.
+pub fn writer() -> MutexGuard<'static, Connection> {
+    // A panic while holding the lock leaves the connection itself usable.
+    get_db()
+        .writer
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Check out a read-only connection from the pool.
+pub fn reader() -> SqliteResult<ReadConnection> {
+    get_db().readers.get().map_err(|e| {
+        rusqlite::Error::SqliteFailure(
+            ffi::Error::new(ffi::SQLITE_BUSY),
+            Some(format!("No database connection available: {}", e)),
+        )
+    })
+}
+
 /// Get a setting value
 pub fn get_setting(key: &str) -> SqliteResult<Option<String>> {
-    let conn = get_db().lock().unwrap();
+    let conn = reader()?;
     let mut stmt = conn.prepare("SELECT value FROM settings WHERE key = ?")?;
     let mut rows = stmt.query([key])?;
 
@@ -74,10 +152,9 @@ pub fn get_setting(key: &str) -> SqliteResult<Option<String>> {
     }
 }
 
-#[allow(dead_code)]
 /// Set a setting value
 pub fn set_setting(key: &str, value: &str) -> SqliteResult<()> {
-    let conn = get_db().lock().unwrap();
+    let conn = writer();
     conn.execute(
         "INSERT OR REPLACE INTO settings (key, value) VALUES (?, ?)",
         [key, value],
@@ -0,0 +1,194 @@
+use rusqlite::ErrorCode;
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+use std::fmt;
+
+/// Error returned by every command, serialized as `{ "code": "...", "message": "..." }` so
+/// the frontend can branch on `code` instead of parsing SQLite strings.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppError {
+    /// The requested todo, research result or file does not exist.
+    NotFound(String),
+    /// The todo's current status does not allow the requested action.
+    InvalidTransition(String),
+    /// Input was rejected, including constraint violations and malformed files.
+    Validation(String),
+    /// Database or filesystem failure, including a locked database.
+    Storage(String),
+    /// The core window or a webview is missing or rejected a script.
+    Webview(String),
+    /// The research provider (ChatGPT) failed or is not usable.
+    Provider(String),
+}
+
+impl AppError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::NotFound(_) => "NotFound",
+            AppError::InvalidTransition(_) => "InvalidTransition",
+            AppError::Validation(_) => "Validation",
+            AppError::Storage(_) => "Storage",
+            AppError::Webview(_) => "Webview",
+            AppError::Provider(_) => "Provider",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            AppError::NotFound(m)
+            | AppError::InvalidTransition(m)
+            | AppError::Validation(m)
+            | AppError::Storage(m)
+            | AppError::Webview(m)
+            | AppError::Provider(m) => m,
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", self.message())?;
+        state.end()
+    }
+}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(err: rusqlite::Error) -> Self {
+        match &err {
+            rusqlite::Error::QueryReturnedNoRows => AppError::NotFound(err.to_string()),
+            rusqlite::Error::SqliteFailure(e, _) => match e.code {
+                ErrorCode::ConstraintViolation => AppError::Validation(err.to_string()),
+                ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked => {
+                    AppError::Storage(format!("Database is locked: {}", err))
+                }
+                _ => AppError::Storage(err.to_string()),
+            },
+            _ => AppError::Storage(err.to_string()),
+        }
+    }
+}
+
+impl From<r2d2::Error> for AppError {
+    fn from(err: r2d2::Error) -> Self {
+        AppError::Storage(err.to_string())
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(err: std::io::Error) -> Self {
+        match err.kind() {
+            std::io::ErrorKind::NotFound => AppError::NotFound(err.to_string()),
+            _ => AppError::Storage(err.to_string()),
+        }
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(err: serde_json::Error) -> Self {
+        AppError::Validation(err.to_string())
+    }
+}
+
+impl From<tauri::Error> for AppError {
+    fn from(err: tauri::Error) -> Self {
+        AppError::Webview(err.to_string())
+    }
+}
+
+impl From<Box<dyn std::error::Error>> for AppError {
+    fn from(err: Box<dyn std::error::Error>) -> Self {
+        AppError::Storage(err.to_string())
+    }
+}
+
+/// Picks the code from the innermost typed error in the chain and keeps the full context
+/// chain as the message.
+impl From<anyhow::Error> for AppError {
+    fn from(err: anyhow::Error) -> Self {
+        if let Some(app_err) = err.downcast_ref::<AppError>() {
+            return app_err.clone();
+        }
+
+        let message = format!("{:#}", err);
+        if let Some(e) = err.downcast_ref::<rusqlite::Error>() {
+            match AppError::from(clone_sqlite_error(e)) {
+                AppError::Storage(_) => AppError::Storage(message),
+                AppError::Validation(_) => AppError::Validation(message),
+                AppError::NotFound(_) => AppError::NotFound(message),
+                other => other,
+            }
+        } else if let Some(e) = err.downcast_ref::<std::io::Error>() {
+            match e.kind() {
+                std::io::ErrorKind::NotFound => AppError::NotFound(message),
+                _ => AppError::Storage(message),
+            }
+        } else if err.downcast_ref::<serde_json::Error>().is_some()
+            || err.downcast_ref::<csv::Error>().is_some()
+        {
+            AppError::Validation(message)
+        } else {
+            AppError::Storage(message)
+        }
+    }
+}
+
+/// `rusqlite::Error` is not `Clone`; keep just enough of it to classify.
+fn clone_sqlite_error(err: &rusqlite::Error) -> rusqlite::Error {
+    match err {
+        rusqlite::Error::QueryReturnedNoRows => rusqlite::Error::QueryReturnedNoRows,
+        rusqlite::Error::SqliteFailure(e, msg) => rusqlite::Error::SqliteFailure(*e, msg.clone()),
+        other => rusqlite::Error::InvalidParameterName(other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::Connection;
+
+    #[test]
+    fn test_sqlite_errors_are_classified() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE t (id TEXT PRIMARY KEY)")
+            .unwrap();
+        conn.execute("INSERT INTO t VALUES ('a')", []).unwrap();
+
+        let dup = conn.execute("INSERT INTO t VALUES ('a')", []).unwrap_err();
+        assert_eq!(AppError::from(dup).code(), "Validation");
+
+        let missing = conn
+            .query_row("SELECT id FROM t WHERE id = 'b'", [], |r| {
+                r.get::<_, String>(0)
+            })
+            .unwrap_err();
+        assert_eq!(AppError::from(missing).code(), "NotFound");
+    }
+
+    #[test]
+    fn test_anyhow_keeps_code_and_context() {
+        let err = anyhow::Error::new(AppError::Validation("bad row".to_string()));
+        assert_eq!(
+            AppError::from(err),
+            AppError::Validation("bad row".to_string())
+        );
+
+        let io = std::io::Error::new(std::io::ErrorKind::NotFound, "gone");
+        let err = anyhow::Error::new(io).context("Failed to read backup.json");
+        let app_err = AppError::from(err);
+        assert_eq!(app_err.code(), "NotFound");
+        assert_eq!(app_err.message(), "Failed to read backup.json: gone");
+
+        let json = serde_json::to_value(AppError::Webview("no window".to_string())).unwrap();
+        assert_eq!(json["code"], "Webview");
+        assert_eq!(json["message"], "no window");
+    }
+}
@@ -0,0 +1,428 @@
+use anyhow::{Context, Result};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+use crate::core::{
+    import::ImportRow,
+    todo::{self, parse_due_date, CreateTodoInput, Todo, TodoStatus},
+};
+
+/// Tag prefix used to carry todo.txt priorities, e.g. `pri:A`.
+pub static PRIORITY_TAG: &str = "pri:";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    TodoTxt,
+    Markdown,
+}
+
+/// Parses todo.txt lines. Contexts are kept as `@context` tags, projects become plain tags
+/// and the priority is stored as a `pri:X` tag.
+pub fn parse_todo_txt(contents: &str) -> Vec<ImportRow> {
+    let mut rows = Vec::new();
+
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let mut errors = Vec::new();
+        let mut tokens = line.split_whitespace().peekable();
+        let mut status = None;
+        let mut tags = Vec::new();
+
+        if tokens.peek() == Some(&"x") {
+            tokens.next();
+            status = Some(TodoStatus::Done);
+            // Completion date, then creation date.
+            for _ in 0..2 {
+                if tokens.peek().is_some_and(|t| is_date(t)) {
+                    tokens.next();
+                }
+            }
+        } else {
+            if let Some(priority) = tokens.peek().and_then(|t| parse_priority(t)) {
+                tags.push(format!("{}{}", PRIORITY_TAG, priority));
+                tokens.next();
+            }
+            if tokens.peek().is_some_and(|t| is_date(t)) {
+                tokens.next();
+            }
+        }
+
+        let mut words = Vec::new();
+        let mut url = None;
+        let mut due_date = None;
+
+        for token in tokens {
+            if let Some(context) = token.strip_prefix('@').filter(|c| !c.is_empty()) {
+                tags.push(format!("@{}", context));
+            } else if let Some(project) = token.strip_prefix('+').filter(|p| !p.is_empty()) {
+                tags.push(project.to_string());
+            } else if let Some(value) = token.strip_prefix("due:") {
+                match parse_due_date(value) {
+                    Some(date) => due_date = Some(date),
+                    None => errors.push(format!("Invalid due date: {}", value)),
+                }
+            } else if let Some(value) = token.strip_prefix(PRIORITY_TAG) {
+                tags.push(format!("{}{}", PRIORITY_TAG, value));
+            } else if url.is_none() && is_url(token) {
+                url = Some(token.to_string());
+            } else {
+                words.push(token);
+            }
+        }
+
+        let input = CreateTodoInput {
+            title: words.join(" "),
+            url,
+            status,
+            tags,
+            due_date,
+            ..Default::default()
+        };
+        rows.push(make_row(rows.len() + 1, input, errors));
+    }
+
+    rows
+}
+
+/// Parses GitHub-style `- [ ]` / `- [x]` checklists. Indented plain lines under an item
+/// become its description.
+pub fn parse_markdown_checklist(contents: &str) -> Vec<ImportRow> {
+    let mut rows: Vec<ImportRow> = Vec::new();
+    let mut description: Vec<String> = Vec::new();
+
+    let flush_description = |rows: &mut Vec<ImportRow>, description: &mut Vec<String>| {
+        if let Some(input) = rows.last_mut().and_then(|r| r.input.as_mut()) {
+            if !description.is_empty() {
+                input.description = Some(description.join("\n"));
+            }
+        }
+        description.clear();
+    };
+
+    for line in contents.lines() {
+        let trimmed = line.trim_start();
+
+        if let Some((done, text)) = checklist_item(trimmed) {
+            flush_description(&mut rows, &mut description);
+
+            let mut errors = Vec::new();
+            let mut tags = Vec::new();
+            let mut due_date = None;
+            let (text, mut url) = extract_link(text);
+
+            let mut words = Vec::new();
+            let mut tokens = text.split_whitespace();
+            while let Some(token) = tokens.next() {
+                if let Some(tag) = token.strip_prefix('#').filter(|t| !t.is_empty()) {
+                    tags.push(tag.to_string());
+                } else if token.len() > 1 && token.starts_with('@') {
+                    tags.push(token.to_string());
+                } else if let Some(value) = token.strip_prefix("due:") {
+                    match parse_due_date(value) {
+                        Some(date) => due_date = Some(date),
+                        None => errors.push(format!("Invalid due date: {}", value)),
+                    }
+                } else if token == "📅" {
+                    let value = tokens.next().unwrap_or("");
+                    match parse_due_date(value) {
+                        Some(date) => due_date = Some(date),
+                        None => errors.push(format!("Invalid due date: {}", value)),
+                    }
+                } else if url.is_none() && is_url(token) {
+                    url = Some(token.to_string());
+                } else {
+                    words.push(token);
+                }
+            }
+
+            let status = done.then_some(TodoStatus::Done);
+            let input = CreateTodoInput {
+                title: words.join(" "),
+                url,
+                status,
+                tags,
+                due_date,
+                ..Default::default()
+            };
+            rows.push(make_row(rows.len() + 1, input, errors));
+        } else if !rows.is_empty() && line.starts_with([' ', '\t']) && !trimmed.is_empty() {
+            description.push(trimmed.trim_end().to_string());
+        } else if !trimmed.is_empty() {
+            // Headings and other prose end the current item's description.
+            flush_description(&mut rows, &mut description);
+        }
+    }
+    flush_description(&mut rows, &mut description);
+
+    rows
+}
+
+/// Renders todos as todo.txt lines. Descriptions have no todo.txt equivalent and are dropped.
+pub fn to_todo_txt(todos: &[Todo]) -> String {
+    let mut out = String::new();
+
+    for todo in todos {
+        let mut parts: Vec<String> = Vec::new();
+        let done = matches!(todo.status, TodoStatus::Done | TodoStatus::Archived);
+        let priority = todo.tags.iter().find_map(|t| t.strip_prefix(PRIORITY_TAG));
+
+        if done {
+            parts.push("x".to_string());
+            parts.push(date_part(&todo.updated_at).to_string());
+        } else if let Some(priority) = priority {
+            parts.push(format!("({})", priority));
+        }
+        parts.push(date_part(&todo.created_at).to_string());
+        parts.push(single_line(&todo.title));
+
+        if let Some(url) = todo.url.as_deref() {
+            parts.push(url.to_string());
+        }
+        for tag in &todo.tags {
+            if tag.starts_with('@') {
+                parts.push(tag.replace(' ', "-"));
+            } else if tag.starts_with(PRIORITY_TAG) {
+                // Priorities only survive completion as a `pri:` tag.
+                if done {
+                    parts.push(tag.clone());
+                }
+            } else {
+                parts.push(format!("+{}", tag.replace(' ', "-")));
+            }
+        }
+        if let Some(due_date) = todo.due_date.as_deref() {
+            parts.push(format!("due:{}", date_part(due_date)));
+        }
+
+        out.push_str(&parts.join(" "));
+        out.push('\n');
+    }
+
+    out
+}
+
+/// Renders todos as a markdown checklist that [`parse_markdown_checklist`] reads back.
+pub fn to_markdown_checklist(todos: &[Todo]) -> String {
+    let mut out = String::new();
+
+    for todo in todos {
+        let done = matches!(todo.status, TodoStatus::Done | TodoStatus::Archived);
+        let title = single_line(&todo.title);
+        let mut line = format!("- [{}] ", if done { "x" } else { " " });
+
+        match todo.url.as_deref() {
+            Some(url) => line.push_str(&format!("[{}]({})", title, url)),
+            None => line.push_str(&title),
+        }
+        for tag in &todo.tags {
+            let tag = tag.replace(' ', "-");
+            if tag.starts_with('@') {
+                line.push_str(&format!(" {}", tag));
+            } else {
+                line.push_str(&format!(" #{}", tag));
+            }
+        }
+        if let Some(due_date) = todo.due_date.as_deref() {
+            line.push_str(&format!(" due:{}", date_part(due_date)));
+        }
+
+        out.push_str(&line);
+        out.push('\n');
+
+        if let Some(description) = todo.description.as_deref() {
+            for desc_line in description.lines().filter(|l| !l.trim().is_empty()) {
+                out.push_str(&format!("  {}\n", desc_line.trim()));
+            }
+        }
+    }
+
+    out
+}
+
+pub fn export(todos: &[Todo], format: ExportFormat) -> String {
+    match format {
+        ExportFormat::TodoTxt => to_todo_txt(todos),
+        ExportFormat::Markdown => to_markdown_checklist(todos),
+    }
+}
+
+/// Writes the todos matching `status` (all non-archived when `None`) to `path`.
+pub fn export_file<P: AsRef<Path>>(
+    path: P,
+    format: ExportFormat,
+    status: Option<&str>,
+) -> Result<usize> {
+    let path = path.as_ref();
+    let todos = todo::get_todos(status)?;
+    fs::write(path, export(&todos, format))
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+
+    info!(
+        "[formats] Exported {} todo(s) to {}",
+        todos.len(),
+        path.display()
+    );
+    Ok(todos.len())
+}
+
+fn make_row(row: usize, input: CreateTodoInput, mut errors: Vec<String>) -> ImportRow {
+    if input.title.trim().is_empty() {
+        errors.push("Missing title".to_string());
+    }
+
+    ImportRow {
+        row,
+        input: Some(input),
+        errors,
+    }
+}
+
+fn checklist_item(line: &str) -> Option<(bool, &str)> {
+    let rest = line
+        .strip_prefix("- ")
+        .or_else(|| line.strip_prefix("* "))
+        .or_else(|| line.strip_prefix("+ "))?;
+
+    if let Some(text) = rest.strip_prefix("[ ]") {
+        Some((false, text.trim()))
+    } else {
+        rest.strip_prefix("[x]")
+            .or_else(|| rest.strip_prefix("[X]"))
+            .map(|text| (true, text.trim()))
+    }
+}
+
+/// Replaces the first `[text](url)` with `text` and returns the url.
+fn extract_link(text: &str) -> (String, Option<String>) {
+    if let Some(start) = text.find('[') {
+        if let Some(mid) = text[start..].find("](") {
+            let mid = start + mid;
+            if let Some(end) = text[mid..].find(')') {
+                let end = mid + end;
+                let label = &text[start + 1..mid];
+                let url = &text[mid + 2..end];
+                let replaced = format!("{}{}{}", &text[..start], label, &text[end + 1..]);
+                return (replaced, Some(url.to_string()));
+            }
+        }
+    }
+    (text.to_string(), None)
+}
+
+fn parse_priority(token: &str) -> Option<char> {
+    let mut chars = token.chars();
+    match (chars.next(), chars.next(), chars.next(), chars.next()) {
+        (Some('('), Some(p), Some(')'), None) if p.is_ascii_uppercase() => Some(p),
+        _ => None,
+    }
+}
+
+fn is_date(token: &str) -> bool {
+    chrono::NaiveDate::parse_from_str(token, "%Y-%m-%d").is_ok()
+}
+
+fn is_url(token: &str) -> bool {
+    token.starts_with("http://") || token.starts_with("https://")
+}
+
+fn date_part(value: &str) -> &str {
+    value.get(..10).unwrap_or(value)
+}
+
+fn single_line(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_todo_txt() {
+        let rows = parse_todo_txt(
+            "(A) 2024-01-02 Call mom @phone +family due:2024-01-05\n\
+             x 2024-01-03 2024-01-01 Review https://example.com report +work\n\
+             (B) due:not-a-date\n",
+        );
+
+        let first = rows[0].input.as_ref().unwrap();
+        assert_eq!(first.title, "Call mom");
+        assert_eq!(first.tags, vec!["pri:A", "@phone", "family"]);
+        assert_eq!(first.due_date.as_deref(), Some("2024-01-05"));
+        assert!(rows[0].errors.is_empty());
+
+        let second = rows[1].input.as_ref().unwrap();
+        assert_eq!(second.title, "Review report");
+        assert_eq!(second.status, Some(TodoStatus::Done));
+        assert_eq!(second.url.as_deref(), Some("https://example.com"));
+
+        assert_eq!(rows[2].errors.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_markdown_checklist() {
+        let rows = parse_markdown_checklist(
+            "# Backlog\n\
+             - [ ] [Market scan](https://a.com) #ev due:2024-02-01\n\
+             \x20 First note\n\
+             \x20 Second note\n\
+             - [x] Done item @desk\n",
+        );
+
+        assert_eq!(rows.len(), 2);
+        let first = rows[0].input.as_ref().unwrap();
+        assert_eq!(first.title, "Market scan");
+        assert_eq!(first.url.as_deref(), Some("https://a.com"));
+        assert_eq!(first.tags, vec!["ev"]);
+        assert_eq!(
+            first.description.as_deref(),
+            Some("First note\nSecond note")
+        );
+
+        let second = rows[1].input.as_ref().unwrap();
+        assert_eq!(second.status, Some(TodoStatus::Done));
+        assert_eq!(second.tags, vec!["@desk"]);
+    }
+
+    #[test]
+    fn test_export_round_trip() {
+        let todo = Todo {
+            id: "t1".to_string(),
+            title: "Market scan".to_string(),
+            description: Some("Notes".to_string()),
+            url: Some("https://a.com".to_string()),
+            status: TodoStatus::Pending,
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            updated_at: "2024-01-01T00:00:00Z".to_string(),
+            tags: vec!["pri:A".to_string(), "@desk".to_string(), "ev".to_string()],
+            due_date: Some("2024-02-01".to_string()),
+            deleted_at: None,
+            recurrence: None,
+            next_run_at: None,
+        };
+
+        let txt = to_todo_txt(std::slice::from_ref(&todo));
+        assert_eq!(
+            txt,
+            "(A) 2024-01-01 Market scan https://a.com @desk +ev due:2024-02-01\n"
+        );
+        let parsed = parse_todo_txt(&txt);
+        let input = parsed[0].input.as_ref().unwrap();
+        assert_eq!(input.tags, todo.tags);
+        assert_eq!(input.url, todo.url);
+
+        let md = to_markdown_checklist(std::slice::from_ref(&todo));
+        let parsed = parse_markdown_checklist(&md);
+        let input = parsed[0].input.as_ref().unwrap();
+        assert_eq!(input.title, todo.title);
+        assert_eq!(input.tags, todo.tags);
+        assert_eq!(input.description, todo.description);
+        assert_eq!(input.due_date, todo.due_date);
+    }
+}
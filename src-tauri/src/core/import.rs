@@ -0,0 +1,380 @@
+use anyhow::{bail, Context, Result};
+use log::info;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{collections::HashMap, fs, path::Path};
+
+use crate::core::{
+    db,
+    error::AppError,
+    formats,
+    todo::{insert_todo, parse_due_date, CreateTodoInput, Todo, TodoStatus},
+};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportFormat {
+    Csv,
+    Json,
+    TodoTxt,
+    Markdown,
+}
+
+impl ImportFormat {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let ext = path.as_ref().extension()?.to_str()?.to_lowercase();
+        match ext.as_str() {
+            "csv" => Some(ImportFormat::Csv),
+            "json" => Some(ImportFormat::Json),
+            "txt" => Some(ImportFormat::TodoTxt),
+            "md" | "markdown" => Some(ImportFormat::Markdown),
+            _ => None,
+        }
+    }
+}
+
+/// Source column (CSV header or JSON key) for each todo field.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImportMapping {
+    pub title: String,
+    pub description: Option<String>,
+    pub url: Option<String>,
+    pub status: Option<String>,
+    pub tags: Option<String>,
+    pub due_date: Option<String>,
+}
+
+impl Default for ImportMapping {
+    fn default() -> Self {
+        Self {
+            title: "title".to_string(),
+            description: Some("description".to_string()),
+            url: Some("url".to_string()),
+            status: Some("status".to_string()),
+            tags: Some("tags".to_string()),
+            due_date: Some("due_date".to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportRow {
+    /// 1-based record number, excluding the CSV header.
+    pub row: usize,
+    pub input: Option<CreateTodoInput>,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportPreview {
+    pub rows: Vec<ImportRow>,
+    pub valid: usize,
+    pub invalid: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportResult {
+    pub created: Vec<Todo>,
+    pub skipped: usize,
+}
+
+/// Parses and validates every record without touching the database. The column mapping
+/// only applies to CSV and JSON.
+pub fn preview(
+    contents: &str,
+    format: ImportFormat,
+    mapping: &ImportMapping,
+) -> Result<ImportPreview> {
+    let rows: Vec<ImportRow> = match format {
+        ImportFormat::Csv => validate_records(&read_csv(contents)?, mapping),
+        ImportFormat::Json => validate_records(&read_json(contents)?, mapping),
+        ImportFormat::TodoTxt => formats::parse_todo_txt(contents),
+        ImportFormat::Markdown => formats::parse_markdown_checklist(contents),
+    };
+
+    let valid = rows.iter().filter(|r| r.errors.is_empty()).count();
+    Ok(ImportPreview {
+        invalid: rows.len() - valid,
+        valid,
+        rows,
+    })
+}
+
+/// Creates every valid row in one transaction. Invalid rows abort the import unless
+/// `skip_invalid` is set.
+pub fn commit(
+    conn: &mut Connection,
+    preview: ImportPreview,
+    skip_invalid: bool,
+) -> Result<ImportResult> {
+    if preview.invalid > 0 && !skip_invalid {
+        bail!(AppError::Validation(format!(
+            "{} row(s) failed validation",
+            preview.invalid
+        )));
+    }
+
+    let tx = conn.transaction()?;
+    let mut created = Vec::new();
+    for row in preview.rows {
+        if let (Some(input), true) = (row.input, row.errors.is_empty()) {
+            let todo = insert_todo(&tx, input)
+                .with_context(|| format!("Failed to import row {}", row.row))?;
+            created.push(todo);
+        }
+    }
+    tx.commit()?;
+
+    Ok(ImportResult {
+        created,
+        skipped: preview.invalid,
+    })
+}
+
+pub fn preview_file<P: AsRef<Path>>(
+    path: P,
+    format: Option<ImportFormat>,
+    mapping: &ImportMapping,
+) -> Result<ImportPreview> {
+    let path = path.as_ref();
+    let format = format
+        .or_else(|| ImportFormat::from_path(path))
+        .ok_or_else(|| {
+            AppError::Validation(format!("Cannot detect import format of {}", path.display()))
+        })?;
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    preview(&contents, format, mapping)
+}
+
+pub fn import_file<P: AsRef<Path>>(
+    path: P,
+    format: Option<ImportFormat>,
+    mapping: &ImportMapping,
+    skip_invalid: bool,
+) -> Result<ImportResult> {
+    let path = path.as_ref();
+    let preview = preview_file(path, format, mapping)?;
+
+    let mut conn = db::writer();
+    let result = commit(&mut conn, preview, skip_invalid)?;
+
+    info!(
+        "[import] Imported {} todo(s) from {}",
+        result.created.len(),
+        path.display()
+    );
+    Ok(result)
+}
+
+type Record = HashMap<String, Value>;
+
+fn read_csv(contents: &str) -> Result<Vec<Record>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(contents.as_bytes());
+    let headers = reader.headers().context("CSV has no header row")?.clone();
+
+    let mut records = Vec::new();
+    for (i, result) in reader.records().enumerate() {
+        let record = result.with_context(|| format!("Malformed CSV at record {}", i + 1))?;
+        records.push(
+            headers
+                .iter()
+                .zip(record.iter())
+                .map(|(h, v)| (h.to_string(), Value::String(v.to_string())))
+                .collect(),
+        );
+    }
+    Ok(records)
+}
+
+fn read_json(contents: &str) -> Result<Vec<Record>> {
+    let value: Value = serde_json::from_str(contents).context("Invalid JSON")?;
+    let items = match value {
+        Value::Array(items) => Some(items),
+        Value::Object(mut obj) => match obj.remove("todos") {
+            Some(Value::Array(items)) => Some(items),
+            _ => None,
+        },
+        _ => None,
+    };
+    let Some(items) = items else {
+        bail!(AppError::Validation(
+            "JSON import must be an array or an object with a \"todos\" array".to_string()
+        ));
+    };
+
+    Ok(items
+        .into_iter()
+        .map(|item| match item {
+            Value::Object(obj) => obj.into_iter().collect(),
+            // Non-objects become records without fields and fail the title check.
+            _ => Record::new(),
+        })
+        .collect())
+}
+
+fn validate_records(records: &[Record], mapping: &ImportMapping) -> Vec<ImportRow> {
+    records
+        .iter()
+        .enumerate()
+        .map(|(i, record)| validate_record(i + 1, record, mapping))
+        .collect()
+}
+
+fn field(record: &Record, column: Option<&String>) -> Option<String> {
+    let value = record.get(column?)?;
+    let text = match value {
+        Value::Null => return None,
+        Value::String(s) => s.trim().to_string(),
+        other => other.to_string(),
+    };
+    (!text.is_empty()).then_some(text)
+}
+
+fn validate_record(row: usize, record: &Record, mapping: &ImportMapping) -> ImportRow {
+    let mut errors = Vec::new();
+
+    let title = field(record, Some(&mapping.title));
+    if title.is_none() {
+        errors.push(format!("Missing title (column \"{}\")", mapping.title));
+    }
+
+    let url = field(record, mapping.url.as_ref());
+    if let Some(url) = url.as_deref() {
+        if !(url.starts_with("http://") || url.starts_with("https://")) {
+            errors.push(format!("Invalid url: {}", url));
+        }
+    }
+
+    let status = field(record, mapping.status.as_ref()).and_then(|s| {
+        match TodoStatus::parse(&s.to_lowercase()) {
+            // Only the research queue may put a todo in this state.
+            Some(TodoStatus::Researching) => {
+                errors.push(format!("Status cannot be imported: {}", s));
+                None
+            }
+            None => {
+                errors.push(format!("Unknown status: {}", s));
+                None
+            }
+            status => status,
+        }
+    });
+
+    let due_date = field(record, mapping.due_date.as_ref()).and_then(|s| {
+        let date = parse_due_date(&s);
+        if date.is_none() {
+            errors.push(format!("Invalid due date: {}", s));
+        }
+        date
+    });
+
+    let tags = match mapping.tags.as_ref().and_then(|c| record.get(c)) {
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(|v| v.as_str())
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect(),
+        Some(Value::String(s)) => split_tags(s),
+        _ => Vec::new(),
+    };
+
+    let input = title.map(|title| CreateTodoInput {
+        title,
+        description: field(record, mapping.description.as_ref()),
+        url,
+        status,
+        tags,
+        due_date,
+    });
+
+    ImportRow { row, input, errors }
+}
+
+fn split_tags(value: &str) -> Vec<String> {
+    value
+        .split([',', ';', '|'])
+        .map(|s| s.trim().trim_start_matches('#').to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_preview_with_mapping() {
+        let csv = "Name,Notes,Link,State,Labels,Due\n\
+                   Market scan,Q3 overview,https://a.com,Review,\"ev, battery\",2024-09-30\n\
+                   ,missing title,,,,\n\
+                   Bad row,,ftp://x,unknown,,tomorrow\n";
+        let mapping = ImportMapping {
+            title: "Name".to_string(),
+            description: Some("Notes".to_string()),
+            url: Some("Link".to_string()),
+            status: Some("State".to_string()),
+            tags: Some("Labels".to_string()),
+            due_date: Some("Due".to_string()),
+        };
+
+        let preview = preview(csv, ImportFormat::Csv, &mapping).unwrap();
+        assert_eq!(preview.valid, 1);
+        assert_eq!(preview.invalid, 2);
+
+        let input = preview.rows[0].input.as_ref().unwrap();
+        assert_eq!(input.title, "Market scan");
+        assert_eq!(input.status, Some(TodoStatus::Review));
+        assert_eq!(input.tags, vec!["ev".to_string(), "battery".to_string()]);
+        assert_eq!(input.due_date.as_deref(), Some("2024-09-30"));
+
+        assert_eq!(preview.rows[2].errors.len(), 3);
+    }
+
+    #[test]
+    fn test_json_commit_in_transaction() {
+        let json = r#"[{"title": "One", "tags": ["a", "b"]}, {"title": "Two", "status": "done"}]"#;
+        let preview = preview(json, ImportFormat::Json, &ImportMapping::default()).unwrap();
+        assert_eq!(preview.valid, 2);
+
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(include_str!("../migrations/001_init.sql"))
+            .unwrap();
+        let result = commit(&mut conn, preview, false).unwrap();
+        assert_eq!(result.created.len(), 2);
+        assert_eq!(result.created[1].status, TodoStatus::Done);
+
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM todos", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn test_invalid_rows_abort_commit() {
+        let json = r#"[{"title": "One"}, {"description": "no title"}]"#;
+        let preview = preview(json, ImportFormat::Json, &ImportMapping::default()).unwrap();
+
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(include_str!("../migrations/001_init.sql"))
+            .unwrap();
+        assert!(commit(&mut conn, preview, false).is_err());
+    }
+
+    #[test]
+    fn test_researching_status_is_rejected() {
+        let json = r#"[{"title": "Running", "status": "Researching"}]"#;
+        let preview = preview(json, ImportFormat::Json, &ImportMapping::default()).unwrap();
+        assert_eq!(preview.invalid, 1);
+        assert_eq!(
+            preview.rows[0].errors,
+            vec!["Status cannot be imported: Researching".to_string()]
+        );
+    }
+}
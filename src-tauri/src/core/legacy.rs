@@ -0,0 +1,151 @@
+use log::info;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+use tauri::{AppHandle, Manager};
+
+use crate::core::{constant::LEGACY_IDENTIFIER, error::AppError};
+
+/// Files only AI Todo writes; they are moved out of the legacy data directory.
+const DATA_ENTRIES: [&str; 4] = ["aitodo.db", "aitodo.db-wal", "aitodo.db-shm", "backups"];
+
+/// Files the ChatGPT app this project was forked from also uses; they are copied so an
+/// installed ChatGPT app keeps working.
+const CONFIG_ENTRIES: [&str; 2] = ["config.json", "scripts"];
+
+/// A pair of config and data directories.
+#[derive(Debug, Clone)]
+pub struct AppDirs {
+    pub config: PathBuf,
+    pub data: PathBuf,
+}
+
+/// Moves AI Todo data from the `com.nofwl.chatgpt` directories shared with the ChatGPT
+/// app into `current`. Runs once: only while the legacy directory still holds
+/// `aitodo.db` and `current` does not. Returns the entries that were moved or copied.
+pub fn migrate_dirs(legacy: &AppDirs, current: &AppDirs) -> io::Result<Vec<PathBuf>> {
+    if !legacy.data.join("aitodo.db").exists() || current.data.join("aitodo.db").exists() {
+        return Ok(Vec::new());
+    }
+    fs::create_dir_all(&current.data)?;
+    fs::create_dir_all(&current.config)?;
+
+    let mut migrated = Vec::new();
+    for entry in DATA_ENTRIES {
+        let (from, to) = (legacy.data.join(entry), current.data.join(entry));
+        if from.exists() && !to.exists() {
+            fs::rename(&from, &to)?;
+            migrated.push(to);
+        }
+    }
+    for entry in CONFIG_ENTRIES {
+        let (from, to) = (legacy.config.join(entry), current.config.join(entry));
+        if from.exists() && !to.exists() {
+            copy_all(&from, &to)?;
+            migrated.push(to);
+        }
+    }
+    Ok(migrated)
+}
+
+fn copy_all(from: &Path, to: &Path) -> io::Result<()> {
+    if from.is_dir() {
+        fs::create_dir_all(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_all(&entry.path(), &to.join(entry.file_name()))?;
+        }
+    } else {
+        fs::copy(from, to)?;
+    }
+    Ok(())
+}
+
+/// The legacy directories and the ones of the current identifier.
+fn app_dirs(app: &AppHandle) -> tauri::Result<(AppDirs, AppDirs)> {
+    let path = app.path();
+    let legacy = AppDirs {
+        config: path.config_dir()?.join(LEGACY_IDENTIFIER),
+        data: path.data_dir()?.join(LEGACY_IDENTIFIER),
+    };
+    let current = AppDirs {
+        config: path.app_config_dir()?,
+        data: path.app_data_dir()?,
+    };
+    Ok((legacy, current))
+}
+
+/// Moves data left in the legacy directories by earlier builds. Must run before the
+/// database is opened.
+pub fn migrate(app: &AppHandle) -> Result<(), AppError> {
+    let (legacy, current) = app_dirs(app).map_err(|e| AppError::Storage(e.to_string()))?;
+    for entry in migrate_dirs(&legacy, &current)? {
+        info!("[legacy] Migrated {}", entry.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dirs(root: &Path, name: &str) -> AppDirs {
+        AppDirs {
+            config: root.join("config").join(name),
+            data: root.join("data").join(name),
+        }
+    }
+
+    #[test]
+    fn test_migrate_dirs_moves_data_and_copies_config() {
+        let root = std::env::temp_dir().join(format!("aitodo-legacy-{}", uuid::Uuid::new_v4()));
+        let legacy = dirs(&root, LEGACY_IDENTIFIER);
+        let current = dirs(&root, "com.aitodo.desktop");
+
+        fs::create_dir_all(legacy.data.join("backups")).unwrap();
+        fs::create_dir_all(legacy.config.join("scripts")).unwrap();
+        fs::write(legacy.data.join("aitodo.db"), "db").unwrap();
+        fs::write(legacy.data.join("backups").join("snapshot.db"), "snap").unwrap();
+        fs::write(legacy.config.join("config.json"), "{}").unwrap();
+        fs::write(legacy.config.join("scripts").join("ask.js"), "// ask").unwrap();
+
+        let migrated = migrate_dirs(&legacy, &current).unwrap();
+        assert_eq!(migrated.len(), 4);
+        assert!(!legacy.data.join("aitodo.db").exists());
+        assert_eq!(
+            fs::read_to_string(current.data.join("backups").join("snapshot.db")).unwrap(),
+            "snap"
+        );
+        // Config stays in place for the ChatGPT app.
+        assert!(legacy.config.join("config.json").exists());
+        assert_eq!(
+            fs::read_to_string(current.config.join("scripts").join("ask.js")).unwrap(),
+            "// ask"
+        );
+
+        // A second run, or a legacy database appearing later, changes nothing.
+        fs::write(legacy.data.join("aitodo.db"), "other").unwrap();
+        assert!(migrate_dirs(&legacy, &current).unwrap().is_empty());
+        assert_eq!(
+            fs::read_to_string(current.data.join("aitodo.db")).unwrap(),
+            "db"
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_dirs_ignores_chatgpt_only_installs() {
+        let root = std::env::temp_dir().join(format!("aitodo-legacy-{}", uuid::Uuid::new_v4()));
+        let legacy = dirs(&root, LEGACY_IDENTIFIER);
+        let current = dirs(&root, "com.aitodo.desktop");
+        fs::create_dir_all(&legacy.config).unwrap();
+        fs::write(legacy.config.join("config.json"), "{}").unwrap();
+
+        assert!(migrate_dirs(&legacy, &current).unwrap().is_empty());
+        assert!(!current.config.exists());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,735 @@
+use log::{error, info};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use rusqlite::{params, Connection, OptionalExtension};
+use scraper::{node::Node, ElementRef, Html};
+use serde::{Deserialize, Serialize};
+use std::ops::Deref;
+
+use crate::core::{db, error::AppError};
+
+/// Bump whenever the output of [`convert`] changes; stored results rendered by an older
+/// version are re-rendered from their `raw_html` on the next start.
+pub const CONVERTER_VERSION: i64 = 1;
+
+const BLOCK_TAGS: [&str; 24] = [
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "details",
+    "div",
+    "dl",
+    "figure",
+    "footer",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "main",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "ul",
+];
+
+/// Page chrome inside a response, such as the "Copy code" button of code blocks.
+const SKIP_TAGS: [&str; 7] = [
+    "button", "noscript", "script", "style", "svg", "template", "textarea",
+];
+
+static BLANK_LINES: Lazy<Regex> = Lazy::new(|| Regex::new(r"\n{3,}").unwrap());
+
+/// A source linked from a report.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Citation {
+    pub url: String,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Converted {
+    pub markdown: String,
+    /// External links in order of first appearance, without duplicates.
+    pub citations: Vec<Citation>,
+}
+
+#[derive(Default)]
+struct Converter {
+    citations: Vec<Citation>,
+    /// Footnote labels and their text, from the response's footnote list.
+    footnotes: Vec<(String, String)>,
+}
+
+fn is_block(name: &str) -> bool {
+    BLOCK_TAGS.contains(&name)
+}
+
+fn has_class(el: ElementRef, class: &str) -> bool {
+    el.value().classes().any(|c| c == class)
+}
+
+fn is_footnote_list(el: ElementRef) -> bool {
+    has_class(el, "footnotes")
+        || el.value().attr("data-footnotes").is_some()
+        || el.value().attr("role") == Some("doc-endnotes")
+}
+
+fn is_footnote_backref(el: ElementRef, href: &str) -> bool {
+    has_class(el, "footnote-backref")
+        || el.value().attr("data-footnote-backref").is_some()
+        || href.starts_with("#fnref")
+        || href.starts_with("#user-content-fnref")
+}
+
+fn is_footnote_ref(el: ElementRef, href: &str) -> bool {
+    has_class(el, "footnote-ref")
+        || el.value().attr("data-footnote-ref").is_some()
+        || href.starts_with("#fn")
+        || href.starts_with("#user-content-fn")
+}
+
+/// `fn1`, `fn-1` and `user-content-fn-1` all become `1`.
+fn footnote_label(id: &str) -> String {
+    let id = id.trim_start_matches('#');
+    let id = id.strip_prefix("user-content-").unwrap_or(id);
+    let id = id.strip_prefix("fn").unwrap_or(id);
+    id.trim_start_matches(['-', ':']).to_string()
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '*' | '_' | '`' | '[' | ']') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Appends text with runs of whitespace collapsed, as a browser would display it.
+fn push_text(out: &mut String, text: &str) {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut space = out.is_empty() || out.ends_with([' ', '\n']);
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !space {
+                collapsed.push(' ');
+            }
+            space = true;
+        } else {
+            collapsed.push(c);
+            space = false;
+        }
+    }
+    out.push_str(&escape(&collapsed));
+}
+
+/// Wraps `content` in an emphasis marker, keeping surrounding spaces outside it.
+fn emphasize(marker: &str, content: &str) -> String {
+    let trimmed = content.trim();
+    if trimmed.is_empty() {
+        return content.to_string();
+    }
+    let lead = if content.starts_with(' ') { " " } else { "" };
+    let trail = if content.ends_with(' ') { " " } else { "" };
+    format!("{lead}{marker}{trimmed}{marker}{trail}")
+}
+
+fn code_span(code: &str) -> String {
+    let fence = if code.contains('`') { "``" } else { "`" };
+    let pad = if code.starts_with('`') || code.ends_with('`') {
+        " "
+    } else {
+        ""
+    };
+    format!("{fence}{pad}{code}{pad}{fence}")
+}
+
+fn language(el: ElementRef) -> Option<String> {
+    el.value().classes().find_map(|class| {
+        class
+            .strip_prefix("language-")
+            .or_else(|| class.strip_prefix("lang-"))
+            .map(str::to_string)
+    })
+}
+
+fn push_paragraph(blocks: &mut Vec<String>, inline: &mut String) {
+    let paragraph = inline.trim();
+    if !paragraph.is_empty() {
+        blocks.push(paragraph.to_string());
+    }
+    inline.clear();
+}
+
+/// Puts `first_prefix` before the first line and indents the others to line up with it.
+fn hang(first_prefix: &str, content: &str) -> String {
+    let indent = " ".repeat(first_prefix.chars().count());
+    let mut out = first_prefix.to_string();
+    for (i, line) in content.lines().enumerate() {
+        if i > 0 {
+            out.push('\n');
+            if !line.is_empty() {
+                out.push_str(&indent);
+            }
+        }
+        out.push_str(line);
+    }
+    out
+}
+
+impl Converter {
+    /// Renders the children of `el` as blocks separated by `separator`; loose inline
+    /// content between blocks becomes a paragraph.
+    fn blocks(&mut self, el: ElementRef, separator: &str) -> String {
+        let mut blocks = Vec::new();
+        let mut inline = String::new();
+        for child in el.children() {
+            match child.value() {
+                Node::Text(text) => push_text(&mut inline, text),
+                Node::Element(_) => {
+                    let Some(child) = ElementRef::wrap(child) else {
+                        continue;
+                    };
+                    if is_block(child.value().name()) {
+                        push_paragraph(&mut blocks, &mut inline);
+                        let block = self.block(child);
+                        if !block.trim().is_empty() {
+                            blocks.push(block);
+                        }
+                    } else {
+                        let rendered = self.inline(child);
+                        inline.push_str(&rendered);
+                    }
+                }
+                _ => {}
+            }
+        }
+        push_paragraph(&mut blocks, &mut inline);
+        blocks.join(separator)
+    }
+
+    fn block(&mut self, el: ElementRef) -> String {
+        if is_footnote_list(el) {
+            self.collect_footnotes(el);
+            return String::new();
+        }
+
+        let name = el.value().name();
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = name[1..].parse().unwrap_or(1);
+                format!("{} {}", "#".repeat(level), self.inline_children(el).trim())
+            }
+            "p" => self.inline_children(el).trim().to_string(),
+            "hr" => "---".to_string(),
+            "ul" => self.list(el, None),
+            "ol" => {
+                let start = el
+                    .value()
+                    .attr("start")
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(1);
+                self.list(el, Some(start))
+            }
+            "pre" => self.code_block(el),
+            "blockquote" => self
+                .blocks(el, "\n\n")
+                .lines()
+                .map(|line| {
+                    if line.is_empty() {
+                        ">".to_string()
+                    } else {
+                        format!("> {}", line)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            "table" => self.table(el),
+            _ => self.blocks(el, "\n\n"),
+        }
+    }
+
+    fn inline_children(&mut self, el: ElementRef) -> String {
+        let mut out = String::new();
+        for child in el.children() {
+            match child.value() {
+                Node::Text(text) => push_text(&mut out, text),
+                Node::Element(_) => {
+                    if let Some(child) = ElementRef::wrap(child) {
+                        let rendered = self.inline(child);
+                        out.push_str(&rendered);
+                    }
+                }
+                _ => {}
+            }
+        }
+        out
+    }
+
+    fn inline(&mut self, el: ElementRef) -> String {
+        let name = el.value().name();
+        if SKIP_TAGS.contains(&name) {
+            return String::new();
+        }
+        match name {
+            "br" => "  \n".to_string(),
+            "strong" | "b" => emphasize("**", &self.inline_children(el)),
+            "em" | "i" => emphasize("*", &self.inline_children(el)),
+            "del" | "s" | "strike" => emphasize("~~", &self.inline_children(el)),
+            "code" => code_span(&el.text().collect::<String>()),
+            "a" => self.link(el),
+            "img" => match el.value().attr("src") {
+                Some(src) => format!(
+                    "![{}]({})",
+                    escape(el.value().attr("alt").unwrap_or_default()),
+                    src
+                ),
+                None => String::new(),
+            },
+            "input" if el.value().attr("type") == Some("checkbox") => {
+                if el.value().attr("checked").is_some() {
+                    "[x] ".to_string()
+                } else {
+                    "[ ] ".to_string()
+                }
+            }
+            _ => self.inline_children(el),
+        }
+    }
+
+    fn link(&mut self, el: ElementRef) -> String {
+        let href = el.value().attr("href").unwrap_or_default().trim();
+        if is_footnote_backref(el, href) {
+            return String::new();
+        }
+        let text = self.inline_children(el);
+        let text = text.trim();
+        if is_footnote_ref(el, href) {
+            let label = text.trim_matches(['[', ']', '\\']);
+            let label = if label.is_empty() {
+                footnote_label(href)
+            } else {
+                label.to_string()
+            };
+            return format!("[^{}]", label);
+        }
+        if !(href.starts_with("http://") || href.starts_with("https://")) {
+            return text.to_string();
+        }
+
+        if !self.citations.iter().any(|c| c.url == href) {
+            self.citations.push(Citation {
+                url: href.to_string(),
+                title: if text.is_empty() {
+                    href.to_string()
+                } else {
+                    el.text().collect::<String>().trim().to_string()
+                },
+            });
+        }
+        let target = if href.contains([' ', '(', ')']) {
+            format!("<{}>", href)
+        } else {
+            href.to_string()
+        };
+        if text.is_empty() {
+            format!("<{}>", href)
+        } else {
+            format!("[{}]({})", text, target)
+        }
+    }
+
+    fn list(&mut self, el: ElementRef, start: Option<u64>) -> String {
+        let mut items = Vec::new();
+        for (i, li) in el
+            .children()
+            .filter_map(ElementRef::wrap)
+            .filter(|child| child.value().name() == "li")
+            .enumerate()
+        {
+            let marker = match start {
+                Some(start) => format!("{}. ", start + i as u64),
+                None => "- ".to_string(),
+            };
+
+            // Items holding paragraphs are loose; anything else keeps nested lists tight.
+            let loose = li
+                .children()
+                .filter_map(ElementRef::wrap)
+                .any(|child| child.value().name() == "p");
+            let content = self.blocks(li, if loose { "\n\n" } else { "\n" });
+            items.push(hang(&marker, &content));
+        }
+        items.join("\n")
+    }
+
+    fn code_block(&mut self, pre: ElementRef) -> String {
+        // ChatGPT puts a header with the language name and a copy button inside <pre>.
+        let code = pre
+            .descendants()
+            .filter_map(ElementRef::wrap)
+            .find(|el| el.value().name() == "code");
+        let text: String = code.unwrap_or(pre).text().collect();
+        let lang = code.and_then(language).or_else(|| language(pre));
+
+        let mut fence = "```".to_string();
+        while text.contains(&fence) {
+            fence.push('`');
+        }
+        format!(
+            "{fence}{}\n{}\n{fence}",
+            lang.unwrap_or_default(),
+            text.trim_end_matches('\n')
+        )
+    }
+
+    fn table(&mut self, el: ElementRef) -> String {
+        let mut rows: Vec<Vec<String>> = Vec::new();
+        let mut aligns: Vec<&str> = Vec::new();
+        for tr in el
+            .descendants()
+            .filter_map(ElementRef::wrap)
+            .filter(|e| e.value().name() == "tr")
+        {
+            let cells: Vec<ElementRef> = tr
+                .children()
+                .filter_map(ElementRef::wrap)
+                .filter(|c| matches!(c.value().name(), "th" | "td"))
+                .collect();
+            if rows.is_empty() {
+                aligns = cells.iter().map(|cell| alignment(*cell)).collect();
+            }
+            rows.push(
+                cells
+                    .into_iter()
+                    .map(|cell| {
+                        self.inline_children(cell)
+                            .trim()
+                            .replace("  \n", "<br>")
+                            .replace('\n', " ")
+                            .replace('|', "\\|")
+                    })
+                    .collect(),
+            );
+        }
+
+        let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+        if columns == 0 {
+            return String::new();
+        }
+        aligns.resize(columns, "---");
+        let line = |cells: &[String]| {
+            let mut padded = cells.to_vec();
+            padded.resize(columns, String::new());
+            format!("| {} |", padded.join(" | "))
+        };
+
+        let mut out = vec![line(&rows[0]), format!("| {} |", aligns.join(" | "))];
+        out.extend(rows[1..].iter().map(|row| line(row)));
+        out.join("\n")
+    }
+
+    fn collect_footnotes(&mut self, el: ElementRef) {
+        let items: Vec<ElementRef> = el
+            .descendants()
+            .filter_map(ElementRef::wrap)
+            .filter(|e| e.value().name() == "li")
+            .collect();
+        for (i, li) in items.into_iter().enumerate() {
+            let label = li
+                .value()
+                .attr("id")
+                .map(footnote_label)
+                .filter(|label| !label.is_empty())
+                .unwrap_or_else(|| (i + 1).to_string());
+            let text = self.blocks(li, " ").replace('\n', " ");
+            self.footnotes.push((label, text.trim().to_string()));
+        }
+    }
+}
+
+fn alignment(cell: ElementRef) -> &'static str {
+    let style = cell
+        .value()
+        .attr("style")
+        .unwrap_or_default()
+        .replace(' ', "");
+    let align = cell.value().attr("align").unwrap_or_default();
+    if align == "center" || style.contains("text-align:center") {
+        ":---:"
+    } else if align == "right" || style.contains("text-align:right") {
+        "---:"
+    } else if align == "left" || style.contains("text-align:left") {
+        ":---"
+    } else {
+        "---"
+    }
+}
+
+/// Converts the HTML of a ChatGPT response into GitHub-flavoured Markdown: headings,
+/// nested and numbered lists, blockquotes, tables, code blocks and footnotes. Linked
+/// sources are collected and listed under a closing "Sources" heading.
+pub fn convert(html: &str) -> Converted {
+    let fragment = Html::parse_fragment(html);
+    let mut converter = Converter::default();
+    let mut markdown = converter.blocks(fragment.root_element(), "\n\n");
+
+    if !converter.footnotes.is_empty() {
+        markdown.push_str("\n\n");
+        let notes: Vec<String> = converter
+            .footnotes
+            .iter()
+            .map(|(label, text)| format!("[^{}]: {}", label, text))
+            .collect();
+        markdown.push_str(&notes.join("\n"));
+    }
+    if !converter.citations.is_empty() {
+        markdown.push_str("\n\n## Sources\n\n");
+        let sources: Vec<String> = converter
+            .citations
+            .iter()
+            .enumerate()
+            .map(|(i, c)| format!("{}. [{}]({})", i + 1, escape(&c.title), c.url))
+            .collect();
+        markdown.push_str(&sources.join("\n"));
+    }
+
+    Converted {
+        markdown: BLANK_LINES
+            .replace_all(markdown.trim(), "\n\n")
+            .into_owned(),
+        citations: converter.citations,
+    }
+}
+
+/// Stale results are converted without holding the writer lock and written this many at a
+/// time, so re-rendering a long history does not hold up other writes.
+const BATCH_SIZE: usize = 20;
+
+fn select_ids(
+    conn: &Connection,
+    filter: &str,
+    args: &[&dyn rusqlite::ToSql],
+) -> Result<Vec<String>, AppError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id FROM research_results
+         WHERE raw_html IS NOT NULL AND raw_html != '' AND {}",
+        filter
+    ))?;
+    let ids = stmt.query_map(args, |row| row.get(0))?;
+    Ok(ids.collect::<Result<_, _>>()?)
+}
+
+/// Re-renders the content of the given results from their raw HTML and returns how many
+/// changed. Results whose HTML converts to nothing keep their content but are stamped
+/// with the current version all the same, so they are not retried on every start.
+fn rerender_ids<W>(
+    reader: &Connection,
+    writer: impl Fn() -> W,
+    ids: &[String],
+) -> Result<usize, AppError>
+where
+    W: Deref<Target = Connection>,
+{
+    let mut rendered = 0;
+    for batch in ids.chunks(BATCH_SIZE) {
+        let mut converted: Vec<(&str, Option<String>)> = Vec::with_capacity(batch.len());
+        for id in batch {
+            let raw_html: Option<String> = reader
+                .query_row(
+                    "SELECT raw_html FROM research_results WHERE id = ?1",
+                    [id],
+                    |row| row.get(0),
+                )
+                .optional()?
+                .flatten();
+            if let Some(raw_html) = raw_html {
+                let markdown = convert(&raw_html).markdown;
+                converted.push((id, Some(markdown).filter(|m| !m.is_empty())));
+            }
+        }
+
+        let conn = writer();
+        let tx = conn.unchecked_transaction()?;
+        for (id, markdown) in &converted {
+            tx.execute(
+                "UPDATE research_results SET content = COALESCE(?1, content), converter_version = ?2
+                 WHERE id = ?3",
+                params![markdown, CONVERTER_VERSION, id],
+            )?;
+        }
+        tx.commit()?;
+        rendered += converted.iter().filter(|(_, m)| m.is_some()).count();
+    }
+    Ok(rendered)
+}
+
+/// Re-renders results converted by an older version of the converter, or in the page
+/// before conversion moved to Rust.
+pub fn rerender_stale<W>(reader: &Connection, writer: impl Fn() -> W) -> Result<usize, AppError>
+where
+    W: Deref<Target = Connection>,
+{
+    let ids = select_ids(
+        reader,
+        "(converter_version IS NULL OR converter_version < ?1)",
+        &[&CONVERTER_VERSION],
+    )?;
+    rerender_ids(reader, writer, &ids)
+}
+
+/// Re-renders every research result of a todo from its raw HTML.
+pub fn rerender_todo<W>(
+    reader: &Connection,
+    writer: impl Fn() -> W,
+    todo_id: &str,
+) -> Result<usize, AppError>
+where
+    W: Deref<Target = Connection>,
+{
+    let ids = select_ids(reader, "todo_id = ?1", &[&todo_id])?;
+    rerender_ids(reader, writer, &ids)
+}
+
+/// Brings stored reports up to the current converter in the background.
+pub fn start() {
+    tauri::async_runtime::spawn_blocking(|| {
+        let rendered = db::reader()
+            .map_err(AppError::from)
+            .and_then(|conn| rerender_stale(&conn, db::writer));
+        match rendered {
+            Ok(0) => {}
+            Ok(count) => info!("[markdown] Re-rendered {} research results", count),
+            Err(e) => error!("[markdown] {}", e),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn md(html: &str) -> String {
+        convert(html).markdown
+    }
+
+    #[test]
+    fn test_blocks_and_inline() {
+        let html = r#"<div class="markdown prose"><h2>Key <em>findings</em></h2>
+            <p>LFP cells last <strong>6000 </strong>cycles, 2*3 = 6.<br>Second line</p>
+            <blockquote><p>Quoted</p><p>Twice</p></blockquote><hr>
+            <p>Use <code>cargo test</code> and <del>not</del> <code>make</code>.</p></div>"#;
+        assert_eq!(
+            md(html),
+            "## Key *findings*\n\nLFP cells last **6000** cycles, 2\\*3 = 6.  \nSecond line\n\n\
+             > Quoted\n>\n> Twice\n\n---\n\nUse `cargo test` and ~~not~~ `make`."
+        );
+    }
+
+    #[test]
+    fn test_lists_keep_nesting_and_numbering() {
+        let html = "<ol start=\"3\"><li>Third<ul><li>nested</li><li>also<ol><li>deep</li></ol></li></ul></li><li>Fourth</li></ol>\
+                    <ul><li><input type=\"checkbox\" checked> done</li><li><p>Loose</p><p>item</p></li></ul>";
+        assert_eq!(
+            md(html),
+            "3. Third\n   - nested\n   - also\n     1. deep\n4. Fourth\n\n- [x] done\n- Loose\n\n  item"
+        );
+    }
+
+    #[test]
+    fn test_tables() {
+        let html = r#"<table><thead><tr><th>Chemistry</th><th style="text-align: right">Cycles</th></tr></thead>
+            <tbody><tr><td>LFP</td><td>6000</td></tr><tr><td>NMC | NCA</td><td>2000<br>typical</td></tr>
+            <tr><td>Lead</td></tr></tbody></table>"#;
+        assert_eq!(
+            md(html),
+            "| Chemistry | Cycles |\n| --- | ---: |\n| LFP | 6000 |\n| NMC \\| NCA | 2000<br>typical |\n| Lead |  |"
+        );
+    }
+
+    #[test]
+    fn test_code_block_skips_chatgpt_header() {
+        let html = r#"<pre><div class="header"><span>rust</span><button>Copy code</button></div><div><code class="language-rust">fn main() {
+    println!("```");
+}
+</code></div></pre>"#;
+        assert_eq!(
+            md(html),
+            "````rust\nfn main() {\n    println!(\"```\");\n}\n````"
+        );
+    }
+
+    #[test]
+    fn test_footnotes_and_citations() {
+        let html = r##"<p>Costs fell 90%<sup><a href="#fn1" id="fnref1" class="footnote-ref">1</a></sup>
+            per <a href="https://www.iea.org/reports/batteries">IEA</a> and
+            <a href="https://www.iea.org/reports/batteries">again</a>; see <a href="#top">top</a>.</p>
+            <section class="footnotes"><ol><li id="fn1"><p>BNEF survey, <a href="https://about.bnef.com/">2023</a>.
+            <a href="#fnref1" class="footnote-backref">↩</a></p></li></ol></section>"##;
+        let converted = convert(html);
+        assert_eq!(
+            converted.markdown,
+            "Costs fell 90%[^1] per [IEA](https://www.iea.org/reports/batteries) and \
+             [again](https://www.iea.org/reports/batteries); see top.\n\n\
+             [^1]: BNEF survey, [2023](https://about.bnef.com/).\n\n\
+             ## Sources\n\n\
+             1. [IEA](https://www.iea.org/reports/batteries)\n\
+             2. [2023](https://about.bnef.com/)"
+        );
+        assert_eq!(converted.citations.len(), 2);
+    }
+
+    #[test]
+    fn test_rerender_stale_results() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(include_str!("../migrations/001_init.sql"))
+            .unwrap();
+        conn.execute_batch(
+            "INSERT INTO todos (id, title, status, created_at, updated_at)
+                 VALUES ('t1', 'Batteries', 'review', '2024-06-01', '2024-06-01');
+             INSERT INTO research_results (id, todo_id, content, raw_html, converter_version)
+                 VALUES ('old', 't1', 'walker output', '<h1>Report</h1><ol><li>a</li><li>b</li></ol>', NULL),
+                        ('current', 't1', 'kept', '<p>x</p>', 1),
+                        ('no-html', 't1', 'no html', NULL, NULL),
+                        ('empty', 't1', 'not empty', '<div></div>', NULL);",
+        )
+        .unwrap();
+
+        let writer = || &conn;
+        assert_eq!(rerender_stale(&conn, writer).unwrap(), 1);
+        let content = |id: &str| -> String {
+            conn.query_row(
+                "SELECT content FROM research_results WHERE id = ?1",
+                [id],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+        assert_eq!(content("old"), "# Report\n\n1. a\n2. b");
+        assert_eq!(content("current"), "kept");
+        assert_eq!(content("no-html"), "no html");
+        assert_eq!(content("empty"), "not empty");
+        let unstamped: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM research_results
+                 WHERE raw_html IS NOT NULL AND converter_version IS NULL",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(unstamped, 0);
+        assert_eq!(rerender_stale(&conn, writer).unwrap(), 0);
+
+        assert_eq!(rerender_todo(&conn, writer, "t1").unwrap(), 2);
+        assert_eq!(content("current"), "x");
+    }
+}
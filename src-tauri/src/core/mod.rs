@@ -1,9 +1,28 @@
+pub mod backup;
+pub mod bulk;
 pub mod cmd;
 pub mod conf;
 pub mod constant;
 pub mod db;
+pub mod error;
+pub mod formats;
+pub mod import;
+pub mod legacy;
+pub mod markdown;
+pub mod notify;
+pub mod pdf;
+pub mod recurrence;
+pub mod reminders;
+pub mod repository;
 pub mod research;
+pub mod rules;
+pub mod scripts;
+pub mod selectors;
+pub mod selftest;
+pub mod settings;
 pub mod setup;
+pub mod snapshot;
 pub mod template;
 pub mod todo;
+pub mod trash;
 pub mod window;
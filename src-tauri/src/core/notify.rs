@@ -0,0 +1,395 @@
+use chrono::{Local, NaiveTime};
+use log::{error, info};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::sync::{Mutex, MutexGuard};
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+use crate::core::{error::AppError, research, settings};
+
+/// How often the outbox is checked for notices that were held back.
+const FLUSH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// A local time range, written as `HH:MM`, during which notifications are held back. A
+/// range whose end is before its start runs past midnight.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QuietHours {
+    pub start: String,
+    pub end: String,
+}
+
+impl QuietHours {
+    fn bounds(&self) -> Result<(NaiveTime, NaiveTime), AppError> {
+        let parse = |value: &str| {
+            NaiveTime::parse_from_str(value, "%H:%M").map_err(|_| {
+                AppError::Validation(format!("Quiet hours need HH:MM times, got '{}'", value))
+            })
+        };
+        Ok((parse(&self.start)?, parse(&self.end)?))
+    }
+
+    pub fn validate(&self) -> Result<(), AppError> {
+        let (start, end) = self.bounds()?;
+        if start == end {
+            return Err(AppError::Validation(
+                "Quiet hours must not start and end at the same time".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        let Ok((start, end)) = self.bounds() else {
+            return false;
+        };
+        if start <= end {
+            start <= time && time < end
+        } else {
+            time >= start || time < end
+        }
+    }
+}
+
+/// Which events notify, stored under `notifications` in the app config.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct NotificationPrefs {
+    pub research_complete: bool,
+    pub research_failed: bool,
+    pub reminders: bool,
+    /// A batch of queued research runs has finished.
+    pub queue_drained: bool,
+    /// Notifications are held during quiet hours and delivered as a summary afterwards.
+    pub quiet_hours: Option<QuietHours>,
+    /// Hold research completions while the research queue is busy and show them as one
+    /// notification once it drains.
+    pub group_completions: bool,
+}
+
+impl Default for NotificationPrefs {
+    fn default() -> Self {
+        Self {
+            research_complete: true,
+            research_failed: true,
+            reminders: true,
+            queue_drained: true,
+            quiet_hours: None,
+            group_completions: true,
+        }
+    }
+}
+
+impl NotificationPrefs {
+    pub fn validate(&self) -> Result<(), AppError> {
+        match &self.quiet_hours {
+            Some(quiet_hours) => quiet_hours.validate(),
+            None => Ok(()),
+        }
+    }
+
+    pub fn allows(&self, kind: NoticeKind) -> bool {
+        match kind {
+            NoticeKind::ResearchComplete => self.research_complete,
+            NoticeKind::ResearchFailed => self.research_failed,
+            NoticeKind::Reminder => self.reminders,
+            NoticeKind::QueueDrained => self.queue_drained,
+        }
+    }
+
+    fn is_quiet(&self) -> bool {
+        self.quiet_hours
+            .as_ref()
+            .is_some_and(|quiet_hours| quiet_hours.contains(Local::now().time()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NoticeKind {
+    ResearchComplete,
+    ResearchFailed,
+    Reminder,
+    QueueDrained,
+}
+
+/// A notification waiting to be shown.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notice {
+    pub kind: NoticeKind,
+    /// Opened when the notification is clicked; `None` for summaries.
+    pub todo_id: Option<String>,
+    pub title: String,
+    pub body: String,
+}
+
+impl Notice {
+    pub fn research_complete(todo_id: &str, todo_title: &str) -> Self {
+        Self {
+            kind: NoticeKind::ResearchComplete,
+            todo_id: Some(todo_id.to_string()),
+            title: "调研完成".to_string(),
+            body: format!("\"{}\" 的调研已完成，点击查看结果", todo_title),
+        }
+    }
+
+    pub fn research_failed(todo_id: &str, todo_title: &str, reason: &str) -> Self {
+        Self {
+            kind: NoticeKind::ResearchFailed,
+            todo_id: Some(todo_id.to_string()),
+            title: "调研失败".to_string(),
+            body: format!("\"{}\" 的调研未能完成：{}", todo_title, reason),
+        }
+    }
+
+    pub fn queue_drained(count: usize) -> Self {
+        Self {
+            kind: NoticeKind::QueueDrained,
+            todo_id: None,
+            title: "调研队列已完成".to_string(),
+            body: format!("队列中的 {} 项调研均已结束", count),
+        }
+    }
+
+    fn summary(kind: NoticeKind, count: usize) -> Self {
+        let (title, body) = match kind {
+            NoticeKind::ResearchComplete => ("调研完成", format!("{} 项调研已完成", count)),
+            NoticeKind::ResearchFailed => ("调研失败", format!("{} 项调研未能完成", count)),
+            NoticeKind::Reminder => ("待办提醒", format!("{} 项待办即将到期或已逾期", count)),
+            NoticeKind::QueueDrained => ("调研队列已完成", "队列中的调研均已结束".to_string()),
+        };
+        Self {
+            kind,
+            todo_id: None,
+            title: title.to_string(),
+            body,
+        }
+    }
+}
+
+static OUTBOX: Lazy<Mutex<Vec<Notice>>> = Lazy::new(Default::default);
+
+fn outbox() -> MutexGuard<'static, Vec<Notice>> {
+    OUTBOX.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+pub fn load_prefs() -> NotificationPrefs {
+    settings::load()
+        .map(|conf| conf.notifications)
+        .unwrap_or_else(|e| {
+            error!("[notify] {}", e);
+            NotificationPrefs::default()
+        })
+}
+
+/// Takes the notices that can be shown now. Research completions stay in the outbox
+/// while `hold_completions`, so a batch ends in one summary instead of a popup per run.
+fn take_ready(outbox: &mut Vec<Notice>, hold_completions: bool) -> Vec<Notice> {
+    if !hold_completions {
+        return std::mem::take(outbox);
+    }
+    let (held, ready) = std::mem::take(outbox)
+        .into_iter()
+        .partition(|notice| notice.kind == NoticeKind::ResearchComplete);
+    *outbox = held;
+    ready
+}
+
+/// Merges notices of the same kind into one summary, in the order the kinds first
+/// appeared. A drained queue already reports its runs, so completions are folded into it.
+pub fn group(mut notices: Vec<Notice>) -> Vec<Notice> {
+    if notices.iter().any(|n| n.kind == NoticeKind::QueueDrained) {
+        notices.retain(|n| n.kind != NoticeKind::ResearchComplete);
+    }
+
+    let mut kinds: Vec<NoticeKind> = Vec::new();
+    for notice in &notices {
+        if !kinds.contains(&notice.kind) {
+            kinds.push(notice.kind);
+        }
+    }
+
+    kinds
+        .into_iter()
+        .filter_map(|kind| {
+            let mut same: Vec<Notice> =
+                notices.iter().filter(|n| n.kind == kind).cloned().collect();
+            match same.len() {
+                1 => same.pop(),
+                count => Some(Notice::summary(kind, count)),
+            }
+        })
+        .collect()
+}
+
+/// Adds `notice` to the outbox without showing it, so several can go out as one summary
+/// on the next `flush`. Returns false when its kind is disabled.
+pub fn push(prefs: &NotificationPrefs, notice: Notice) -> bool {
+    if !prefs.allows(notice.kind) {
+        info!("[notify] Suppressed {:?}: {}", notice.kind, notice.body);
+        return false;
+    }
+    outbox().push(notice);
+    true
+}
+
+/// The single entry point for single system notifications. Notices of disabled kinds are
+/// dropped; research completions are held until the research queue is idle, and
+/// everything else goes out right away unless quiet hours hold it back.
+pub fn send(app: &AppHandle, notice: Notice) {
+    let prefs = load_prefs();
+    let defer = notice.kind == NoticeKind::ResearchComplete && prefs.group_completions;
+    if push(&prefs, notice) && !defer {
+        flush(app, &prefs);
+    }
+}
+
+/// Called when the research queue goes idle after `finished` runs. Completions held
+/// during a batch go out together with the batch summary.
+pub fn research_idle(app: &AppHandle, finished: usize) {
+    let prefs = load_prefs();
+    // Single runs already notify on completion; only batches get a summary.
+    if finished > 1 {
+        push(&prefs, Notice::queue_drained(finished));
+    }
+    flush(app, &prefs);
+}
+
+/// Shows everything waiting in the outbox, unless it is quiet hours. Research completions
+/// stay while the research queue is busy.
+pub fn flush(app: &AppHandle, prefs: &NotificationPrefs) {
+    if prefs.is_quiet() {
+        return;
+    }
+
+    let hold_completions = prefs.group_completions && research::is_busy();
+    let notices = take_ready(&mut outbox(), hold_completions);
+    for notice in group(notices) {
+        let mut builder = app
+            .notification()
+            .builder()
+            .title(&notice.title)
+            .body(&notice.body);
+        if let Some(todo_id) = &notice.todo_id {
+            builder = builder.extra("todoId", todo_id);
+        }
+        if let Err(e) = builder.show() {
+            error!("[notify] Failed to show '{}': {}", notice.title, e);
+        }
+    }
+}
+
+/// Delivers grouped completions and anything held back during quiet hours.
+pub fn start(app: &AppHandle) {
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(FLUSH_INTERVAL).await;
+            if !outbox().is_empty() {
+                flush(&handle, &load_prefs());
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(s: &str) -> NaiveTime {
+        NaiveTime::parse_from_str(s, "%H:%M").unwrap()
+    }
+
+    #[test]
+    fn test_quiet_hours() {
+        let night = QuietHours {
+            start: "22:00".to_string(),
+            end: "07:30".to_string(),
+        };
+        assert!(night.validate().is_ok());
+        assert!(night.contains(time("23:15")));
+        assert!(night.contains(time("03:00")));
+        assert!(!night.contains(time("07:30")));
+        assert!(!night.contains(time("12:00")));
+
+        let lunch = QuietHours {
+            start: "12:00".to_string(),
+            end: "13:00".to_string(),
+        };
+        assert!(lunch.contains(time("12:30")));
+        assert!(!lunch.contains(time("23:00")));
+
+        let invalid = QuietHours {
+            start: "10pm".to_string(),
+            end: "07:00".to_string(),
+        };
+        assert_eq!(invalid.validate().unwrap_err().code(), "Validation");
+        assert!(!invalid.contains(time("23:00")));
+    }
+
+    #[test]
+    fn test_prefs_default_missing_fields() {
+        let prefs: NotificationPrefs = serde_json::from_str(r#"{"reminders": false}"#).unwrap();
+        assert!(!prefs.allows(NoticeKind::Reminder));
+        assert!(prefs.allows(NoticeKind::ResearchComplete));
+        assert!(prefs.group_completions);
+    }
+
+    #[test]
+    fn test_group_summarizes_each_kind() {
+        let mut notices: Vec<Notice> = (0..10)
+            .map(|i| Notice::research_complete(&format!("t{}", i), "Topic"))
+            .collect();
+        notices.insert(3, Notice::research_failed("f", "Broken", "Not logged in"));
+
+        let grouped = group(notices.clone());
+        assert_eq!(grouped.len(), 2);
+        assert_eq!(
+            grouped[0],
+            Notice::summary(NoticeKind::ResearchComplete, 10)
+        );
+        assert_eq!(grouped[1].todo_id.as_deref(), Some("f"));
+
+        // A drained queue reports the completions itself.
+        notices.push(Notice::queue_drained(11));
+        let grouped = group(notices);
+        assert_eq!(grouped.len(), 2);
+        assert_eq!(grouped[0].todo_id.as_deref(), Some("f"));
+        assert_eq!(grouped[1], Notice::queue_drained(11));
+    }
+
+    #[test]
+    fn test_batch_ends_in_one_notification() {
+        // Ten queued runs complete over minutes; every periodic flush while the queue is
+        // busy shows nothing.
+        let mut outbox = Vec::new();
+        for i in 0..10 {
+            outbox.push(Notice::research_complete(&format!("t{}", i), "Topic"));
+            assert!(group(take_ready(&mut outbox, true)).is_empty());
+        }
+        assert_eq!(outbox.len(), 10);
+
+        // A failure in the middle of the batch is not held back.
+        outbox.push(Notice::research_failed("f", "Broken", "Not logged in"));
+        let shown = group(take_ready(&mut outbox, true));
+        assert_eq!(shown.len(), 1);
+        assert_eq!(shown[0].kind, NoticeKind::ResearchFailed);
+
+        // The queue drains: one summary for the whole batch.
+        outbox.push(Notice::queue_drained(11));
+        let shown = group(take_ready(&mut outbox, false));
+        assert_eq!(shown, vec![Notice::queue_drained(11)]);
+        assert!(outbox.is_empty());
+
+        // Reminders caught up at launch are pushed first and flushed once.
+        let reminders: Vec<Notice> = (0..5)
+            .map(|i| Notice {
+                kind: NoticeKind::Reminder,
+                todo_id: Some(format!("r{}", i)),
+                title: "待办已逾期".to_string(),
+                body: String::new(),
+            })
+            .collect();
+        let shown = group(take_ready(&mut reminders.clone(), true));
+        assert_eq!(shown, vec![Notice::summary(NoticeKind::Reminder, 5)]);
+    }
+}
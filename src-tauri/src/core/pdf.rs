@@ -0,0 +1,798 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use log::{info, warn};
+use once_cell::sync::Lazy;
+use printpdf::link_annotation::{Actions, BorderArray, LinkAnnotation};
+use printpdf::{
+    BuiltinFont, IndirectFontRef, Line, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference,
+    Point, Rect,
+};
+use regex::Regex;
+use std::{fs, path::Path};
+
+use crate::core::todo::{ResearchResult, Todo};
+
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 20.0;
+const LINE_SPACING: f32 = 1.45;
+const PT_TO_MM: f32 = 0.352_778;
+
+const BODY_SIZE: f32 = 10.5;
+const CODE_SIZE: f32 = 9.0;
+const LIST_INDENT: f32 = 6.0;
+
+/// Fonts that can render CJK text, tried in order before falling back to the built-in
+/// Helvetica family (which only covers Latin-1).
+const UNICODE_FONT_CANDIDATES: &[&str] = &[
+    "/System/Library/Fonts/Supplemental/Arial Unicode.ttf",
+    "/Library/Fonts/Arial Unicode.ttf",
+    "C:\\Windows\\Fonts\\simhei.ttf",
+    "C:\\Windows\\Fonts\\arialuni.ttf",
+    "/usr/share/fonts/truetype/droid/DroidSansFallbackFull.ttf",
+    "/usr/share/fonts/truetype/arphic/uming.ttc",
+    "/usr/share/fonts/truetype/wqy/wqy-microhei.ttc",
+];
+
+/// A single link collected from the report body, rendered in the citations appendix.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Citation {
+    pub title: String,
+    pub url: String,
+}
+
+/// Block-level markdown elements supported by the PDF renderer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Block {
+    Heading(usize, String),
+    Paragraph(String),
+    ListItem {
+        depth: usize,
+        marker: String,
+        text: String,
+    },
+    Code(Vec<String>),
+    Quote(String),
+    Rule,
+}
+
+/// Renders a research report into PDF bytes, with a cover page and a citations appendix.
+pub fn render_report(todo: &Todo, research: &ResearchResult) -> Result<Vec<u8>> {
+    let font_data = UNICODE_FONT_CANDIDATES
+        .iter()
+        .map(Path::new)
+        .find(|p| p.exists())
+        .and_then(|p| match fs::read(p) {
+            Ok(data) => {
+                info!("[pdf] Using font: {}", p.display());
+                Some(data)
+            }
+            Err(e) => {
+                warn!("[pdf] Failed to read font {}: {}", p.display(), e);
+                None
+            }
+        });
+
+    render_report_with_font(todo, research, font_data)
+}
+
+/// Same as [`render_report`] but with an explicit TrueType font, or the built-in fonts
+/// when `font_data` is `None`.
+pub fn render_report_with_font(
+    todo: &Todo,
+    research: &ResearchResult,
+    font_data: Option<Vec<u8>>,
+) -> Result<Vec<u8>> {
+    let content = research.content.as_deref().unwrap_or("");
+    let (blocks, citations) = parse_markdown(content);
+
+    let mut writer = PdfWriter::new(&todo.title, font_data)?;
+    writer.cover_page(todo, research);
+
+    writer.new_page();
+    for block in &blocks {
+        writer.block(block);
+    }
+
+    if !citations.is_empty() {
+        writer.citations(&citations);
+    }
+
+    writer
+        .doc
+        .save_to_bytes()
+        .context("Failed to serialize PDF document")
+}
+
+/// Renders the report and writes it to `path`.
+pub fn export_report<P: AsRef<Path>>(
+    todo: &Todo,
+    research: &ResearchResult,
+    path: P,
+) -> Result<()> {
+    let path = path.as_ref();
+    let bytes = render_report(todo, research)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, bytes).with_context(|| format!("Failed to write {}", path.display()))?;
+    info!("[pdf] Exported report: {}", path.display());
+    Ok(())
+}
+
+/// Splits markdown into renderable blocks and replaces inline links with numbered
+/// citation markers.
+pub fn parse_markdown(markdown: &str) -> (Vec<Block>, Vec<Citation>) {
+    let mut blocks = Vec::new();
+    let mut citations = Vec::new();
+    let mut paragraph: Vec<String> = Vec::new();
+    let mut code: Option<Vec<String>> = None;
+
+    let flush = |paragraph: &mut Vec<String>, blocks: &mut Vec<Block>, citations: &mut Vec<_>| {
+        if !paragraph.is_empty() {
+            let text = inline_text(&paragraph.join(" "), citations);
+            blocks.push(Block::Paragraph(text));
+            paragraph.clear();
+        }
+    };
+
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+
+        if trimmed.starts_with("```") {
+            match code.take() {
+                Some(lines) => blocks.push(Block::Code(lines)),
+                None => {
+                    flush(&mut paragraph, &mut blocks, &mut citations);
+                    code = Some(Vec::new());
+                }
+            }
+            continue;
+        }
+
+        if let Some(lines) = code.as_mut() {
+            lines.push(line.to_string());
+            continue;
+        }
+
+        if trimmed.is_empty() {
+            flush(&mut paragraph, &mut blocks, &mut citations);
+            continue;
+        }
+
+        let hashes = trimmed.chars().take_while(|c| *c == '#').count();
+        if (1..=6).contains(&hashes) && trimmed[hashes..].starts_with(' ') {
+            flush(&mut paragraph, &mut blocks, &mut citations);
+            let text = inline_text(trimmed[hashes..].trim(), &mut citations);
+            blocks.push(Block::Heading(hashes, text));
+            continue;
+        }
+
+        if matches!(trimmed, "---" | "***" | "___") {
+            flush(&mut paragraph, &mut blocks, &mut citations);
+            blocks.push(Block::Rule);
+            continue;
+        }
+
+        if let Some(quote) = trimmed.strip_prefix('>') {
+            flush(&mut paragraph, &mut blocks, &mut citations);
+            blocks.push(Block::Quote(inline_text(quote.trim(), &mut citations)));
+            continue;
+        }
+
+        if let Some((marker, text)) = list_marker(trimmed) {
+            flush(&mut paragraph, &mut blocks, &mut citations);
+            let indent = line.len() - trimmed.len();
+            blocks.push(Block::ListItem {
+                depth: indent / 2,
+                marker,
+                text: inline_text(text, &mut citations),
+            });
+            continue;
+        }
+
+        paragraph.push(trimmed.trim_end().to_string());
+    }
+
+    if let Some(lines) = code {
+        blocks.push(Block::Code(lines));
+    }
+    flush(&mut paragraph, &mut blocks, &mut citations);
+
+    (blocks, citations)
+}
+
+fn list_marker(line: &str) -> Option<(String, &str)> {
+    for bullet in ["- ", "* ", "+ "] {
+        if let Some(rest) = line.strip_prefix(bullet) {
+            return Some(("•".to_string(), rest));
+        }
+    }
+
+    let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits > 0 {
+        let rest = &line[digits..];
+        if let Some(text) = rest.strip_prefix(". ").or_else(|| rest.strip_prefix(") ")) {
+            return Some((format!("{}.", &line[..digits]), text));
+        }
+    }
+
+    None
+}
+
+/// Strips emphasis and code markers and turns `[text](url)` into `text [n]`.
+fn inline_text(text: &str, citations: &mut Vec<Citation>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('[') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+
+        // The label ends at the nearest `]`, and only counts as a link when `(` follows.
+        let link = after
+            .find(']')
+            .filter(|&close| !after[..close].contains('[') && after[close + 1..].starts_with('('))
+            .and_then(|close| {
+                let url_part = &after[close + 2..];
+                url_part
+                    .find(')')
+                    .map(|end| (&after[..close], &url_part[..end], &url_part[end + 1..]))
+            });
+
+        match link {
+            Some((label, url, remaining)) if !url.is_empty() && !url.starts_with('#') => {
+                let index = match citations.iter().position(|c| c.url == url) {
+                    Some(i) => i + 1,
+                    None => {
+                        citations.push(Citation {
+                            title: strip_emphasis(label),
+                            url: url.to_string(),
+                        });
+                        citations.len()
+                    }
+                };
+                out.push_str(label);
+                out.push_str(&format!(" [{}]", index));
+                rest = remaining;
+            }
+            Some((label, _, remaining)) => {
+                out.push_str(label);
+                rest = remaining;
+            }
+            None => {
+                out.push('[');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+
+    strip_emphasis(&out)
+}
+
+static CODE_SPAN: Lazy<Regex> = Lazy::new(|| Regex::new(r"`([^`]+)`").unwrap());
+
+/// Paired delimiters that open after a word boundary and close before one, so `2 * 3` and
+/// `foo__bar__baz` are left alone. The boundaries are part of the match and put back.
+static EMPHASIS: Lazy<[Regex; 3]> = Lazy::new(|| {
+    [r"\*\*", "__", r"\*"].map(|delimiter| {
+        Regex::new(&format!(
+            r#"(^|[\s(\["'])(?:{d})(\S|\S.*?\S)(?:{d})($|[\s.,;:!?)\]"'])"#,
+            d = delimiter
+        ))
+        .unwrap()
+    })
+});
+
+fn strip_paired(text: &str) -> String {
+    let mut text = text.to_string();
+    for re in EMPHASIS.iter() {
+        // Adjacent spans share a boundary, which one pass consumes.
+        loop {
+            let next = re.replace_all(&text, "$1$2$3").into_owned();
+            if next == text {
+                break;
+            }
+            text = next;
+        }
+    }
+    text
+}
+
+/// Removes markdown emphasis and code markers. Code spans are kept verbatim.
+fn strip_emphasis(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    for code in CODE_SPAN.captures_iter(text) {
+        let span = code.get(0).unwrap();
+        out.push_str(&strip_paired(&text[last..span.start()]));
+        out.push_str(&code[1]);
+        last = span.end();
+    }
+    out.push_str(&strip_paired(&text[last..]));
+    out
+}
+
+#[derive(Clone, Copy)]
+enum Style {
+    Regular,
+    Bold,
+    Mono,
+}
+
+struct Fonts {
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+    mono: IndirectFontRef,
+    /// External fonts cover CJK, but we can only estimate glyph widths either way.
+    external: bool,
+}
+
+struct PdfWriter {
+    doc: PdfDocumentReference,
+    layer: PdfLayerReference,
+    fonts: Fonts,
+    y: f32,
+}
+
+impl PdfWriter {
+    fn new(title: &str, font_data: Option<Vec<u8>>) -> Result<Self> {
+        let (doc, page, layer) = PdfDocument::new(title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Cover");
+        let layer = doc.get_page(page).get_layer(layer);
+
+        let fonts = match font_data {
+            Some(data) => {
+                let font = doc
+                    .add_external_font(data.as_slice())
+                    .context("Failed to load external font")?;
+                Fonts {
+                    regular: font.clone(),
+                    bold: font.clone(),
+                    mono: font,
+                    external: true,
+                }
+            }
+            None => Fonts {
+                regular: doc.add_builtin_font(BuiltinFont::Helvetica)?,
+                bold: doc.add_builtin_font(BuiltinFont::HelveticaBold)?,
+                mono: doc.add_builtin_font(BuiltinFont::Courier)?,
+                external: false,
+            },
+        };
+
+        Ok(Self {
+            doc,
+            layer,
+            fonts,
+            y: PAGE_HEIGHT - MARGIN,
+        })
+    }
+
+    fn new_page(&mut self) {
+        let (page, layer) = self
+            .doc
+            .add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Content");
+        self.layer = self.doc.get_page(page).get_layer(layer);
+        self.y = PAGE_HEIGHT - MARGIN;
+    }
+
+    fn font(&self, style: Style) -> &IndirectFontRef {
+        match style {
+            Style::Regular => &self.fonts.regular,
+            Style::Bold => &self.fonts.bold,
+            Style::Mono => &self.fonts.mono,
+        }
+    }
+
+    fn ensure_space(&mut self, height: f32) {
+        if self.y - height < MARGIN {
+            self.new_page();
+        }
+    }
+
+    fn gap(&mut self, mm: f32) {
+        self.y -= mm;
+    }
+
+    /// Writes one already-wrapped line and returns its baseline.
+    fn line(&mut self, text: &str, style: Style, size: f32, indent: f32) -> f32 {
+        let height = size * PT_TO_MM * LINE_SPACING;
+        self.ensure_space(height);
+        self.y -= height;
+        self.layer.use_text(
+            text,
+            size,
+            Mm(MARGIN + indent),
+            Mm(self.y),
+            self.font(style),
+        );
+        self.y
+    }
+
+    fn paragraph(&mut self, text: &str, style: Style, size: f32, indent: f32) {
+        let max_width = PAGE_WIDTH - 2.0 * MARGIN - indent;
+        for line in wrap(text, size, max_width, self.char_width(style)) {
+            self.line(&line, style, size, indent);
+        }
+    }
+
+    fn char_width(&self, style: Style) -> f32 {
+        match (style, self.fonts.external) {
+            (Style::Mono, false) => 0.6,
+            _ => 0.55,
+        }
+    }
+
+    fn rule(&mut self) {
+        self.ensure_space(4.0);
+        self.gap(2.0);
+        self.layer.add_line(Line {
+            points: vec![
+                (Point::new(Mm(MARGIN), Mm(self.y)), false),
+                (Point::new(Mm(PAGE_WIDTH - MARGIN), Mm(self.y)), false),
+            ],
+            is_closed: false,
+        });
+        self.gap(2.0);
+    }
+
+    fn cover_page(&mut self, todo: &Todo, research: &ResearchResult) {
+        self.y = PAGE_HEIGHT - 80.0;
+        self.paragraph(&todo.title, Style::Bold, 24.0, 0.0);
+        self.gap(4.0);
+        self.rule();
+
+        if let Some(description) = todo.description.as_deref().filter(|d| !d.is_empty()) {
+            self.gap(2.0);
+            self.paragraph(description, Style::Regular, 12.0, 0.0);
+        }
+
+        self.gap(10.0);
+        let mut meta = vec![
+            ("Source", research.source.clone()),
+            ("Status", todo.status.as_str().to_string()),
+        ];
+        if let Some(url) = todo.url.as_deref() {
+            meta.push(("URL", url.to_string()));
+        }
+        if !todo.tags.is_empty() {
+            meta.push(("Tags", todo.tags.join(", ")));
+        }
+        if let Some(due_date) = todo.due_date.as_deref() {
+            meta.push(("Due", due_date.to_string()));
+        }
+        if let Some(started) = research.started_at.as_deref() {
+            meta.push(("Started", started.to_string()));
+        }
+        if let Some(completed) = research.completed_at.as_deref() {
+            meta.push(("Completed", completed.to_string()));
+        }
+        if let Some(seconds) = research.duration_seconds {
+            meta.push(("Duration", format_duration(seconds)));
+        }
+        meta.push(("Generated", Utc::now().to_rfc3339()));
+
+        for (label, value) in meta {
+            self.paragraph(&format!("{}: {}", label, value), Style::Regular, 11.0, 0.0);
+        }
+    }
+
+    fn block(&mut self, block: &Block) {
+        match block {
+            Block::Heading(level, text) => {
+                let size = match level {
+                    1 => 18.0,
+                    2 => 15.0,
+                    3 => 13.0,
+                    _ => 11.5,
+                };
+                self.ensure_space(size * PT_TO_MM * LINE_SPACING * 3.0);
+                self.gap(3.0);
+                self.paragraph(text, Style::Bold, size, 0.0);
+                self.gap(1.0);
+            }
+            Block::Paragraph(text) => {
+                self.paragraph(text, Style::Regular, BODY_SIZE, 0.0);
+                self.gap(2.0);
+            }
+            Block::ListItem {
+                depth,
+                marker,
+                text,
+            } => {
+                let indent = LIST_INDENT * (*depth as f32);
+                let max_width = PAGE_WIDTH - 2.0 * MARGIN - indent - LIST_INDENT;
+                let lines = wrap(text, BODY_SIZE, max_width, self.char_width(Style::Regular));
+                for (i, line) in lines.iter().enumerate() {
+                    if i == 0 {
+                        let y = self.line("", Style::Regular, BODY_SIZE, indent);
+                        self.layer.use_text(
+                            marker.as_str(),
+                            BODY_SIZE,
+                            Mm(MARGIN + indent),
+                            Mm(y),
+                            &self.fonts.regular,
+                        );
+                        self.layer.use_text(
+                            line.as_str(),
+                            BODY_SIZE,
+                            Mm(MARGIN + indent + LIST_INDENT),
+                            Mm(y),
+                            &self.fonts.regular,
+                        );
+                    } else {
+                        self.line(line, Style::Regular, BODY_SIZE, indent + LIST_INDENT);
+                    }
+                }
+                self.gap(1.0);
+            }
+            Block::Code(lines) => {
+                self.gap(1.0);
+                let max_width = PAGE_WIDTH - 2.0 * MARGIN - LIST_INDENT;
+                let char_width = self.char_width(Style::Mono);
+                for line in lines {
+                    let line = line.replace('\t', "    ");
+                    for part in wrap_code(&line, CODE_SIZE, max_width, char_width) {
+                        self.line(&part, Style::Mono, CODE_SIZE, LIST_INDENT);
+                    }
+                }
+                self.gap(2.0);
+            }
+            Block::Quote(text) => {
+                self.paragraph(text, Style::Regular, BODY_SIZE, LIST_INDENT);
+                self.gap(2.0);
+            }
+            Block::Rule => self.rule(),
+        }
+    }
+
+    fn citations(&mut self, citations: &[Citation]) {
+        self.new_page();
+        self.paragraph("Citations", Style::Bold, 15.0, 0.0);
+        self.gap(2.0);
+
+        for (i, citation) in citations.iter().enumerate() {
+            let title = if citation.title.trim().is_empty() {
+                citation.url.as_str()
+            } else {
+                citation.title.trim()
+            };
+            self.paragraph(
+                &format!("[{}] {}", i + 1, title),
+                Style::Regular,
+                BODY_SIZE,
+                0.0,
+            );
+
+            let max_width = PAGE_WIDTH - 2.0 * MARGIN - LIST_INDENT;
+            let char_width = self.char_width(Style::Regular);
+            for line in wrap(&citation.url, CODE_SIZE, max_width, char_width) {
+                let y = self.line(&line, Style::Regular, CODE_SIZE, LIST_INDENT);
+                let width = text_width(&line, CODE_SIZE, char_width);
+                self.layer.add_link_annotation(LinkAnnotation::new(
+                    Rect::new(
+                        Mm(MARGIN + LIST_INDENT),
+                        Mm(y - 1.0),
+                        Mm(MARGIN + LIST_INDENT + width),
+                        Mm(y + CODE_SIZE * PT_TO_MM),
+                    ),
+                    Some(BorderArray::Solid([0.0, 0.0, 0.0])),
+                    None,
+                    Actions::uri(citation.url.clone()),
+                    None,
+                ));
+            }
+            self.gap(1.5);
+        }
+    }
+}
+
+fn format_duration(seconds: i64) -> String {
+    if seconds >= 3600 {
+        format!("{}h {}m", seconds / 3600, (seconds % 3600) / 60)
+    } else if seconds >= 60 {
+        format!("{}m {}s", seconds / 60, seconds % 60)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+fn is_wide(c: char) -> bool {
+    matches!(c as u32,
+        0x1100..=0x115F | 0x2E80..=0xA4CF | 0xAC00..=0xD7A3 | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F | 0xFF00..=0xFF60 | 0xFFE0..=0xFFE6)
+}
+
+/// Estimated rendered width in mm. Fonts are not measured, so Latin glyphs use an average
+/// advance and CJK glyphs a full em.
+fn text_width(text: &str, size: f32, char_width: f32) -> f32 {
+    text.chars()
+        .map(|c| if is_wide(c) { 1.0 } else { char_width })
+        .sum::<f32>()
+        * size
+        * PT_TO_MM
+}
+
+/// Greedy line wrapping that breaks at spaces, or between any two CJK glyphs.
+fn wrap(text: &str, size: f32, max_width: f32, char_width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    let mut width = 0.0;
+    // Byte offset in `current` after which the line may be broken.
+    let mut break_at: Option<usize> = None;
+
+    for c in text.chars() {
+        let w = if is_wide(c) { 1.0 } else { char_width } * size * PT_TO_MM;
+
+        if width + w > max_width && !current.is_empty() {
+            let split = break_at.unwrap_or(current.len());
+            let remainder = current.split_off(split);
+            lines.push(current.trim_end().to_string());
+            current = remainder.trim_start().to_string();
+            width = text_width(&current, size, char_width);
+            break_at = None;
+        }
+
+        if c == ' ' && current.is_empty() {
+            continue;
+        }
+
+        current.push(c);
+        width += w;
+
+        if c == ' ' || is_wide(c) {
+            break_at = Some(current.len());
+        }
+    }
+
+    if !current.trim().is_empty() || lines.is_empty() {
+        lines.push(current.trim_end().to_string());
+    }
+
+    lines
+}
+
+/// Wrapping for code lines: keeps leading whitespace, breaks anywhere, and repeats the
+/// indent on continuation lines.
+fn wrap_code(text: &str, size: f32, max_width: f32, char_width: f32) -> Vec<String> {
+    let indent: String = text.chars().take_while(|c| *c == ' ').collect();
+    let indent_width = text_width(&indent, size, char_width);
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    let mut width = 0.0;
+
+    for c in text.trim_end().chars() {
+        let w = if is_wide(c) { 1.0 } else { char_width } * size * PT_TO_MM;
+        if width + w > max_width && current.len() > indent.len() {
+            lines.push(std::mem::replace(&mut current, indent.clone()));
+            width = indent_width;
+        }
+        current.push(c);
+        width += w;
+    }
+    lines.push(current);
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::todo::TodoStatus;
+
+    #[test]
+    fn test_parse_markdown_blocks_and_citations() {
+        let md = "# Title\n\nSome **bold** text with [a link](https://a.com).\n\n\
+                  - one\n  - nested [again](https://a.com)\n2. second\n\n```rust\nfn main() {}\n```";
+        let (blocks, citations) = parse_markdown(md);
+
+        assert_eq!(blocks[0], Block::Heading(1, "Title".to_string()));
+        assert_eq!(
+            blocks[1],
+            Block::Paragraph("Some bold text with a link [1].".to_string())
+        );
+        assert_eq!(
+            blocks[3],
+            Block::ListItem {
+                depth: 1,
+                marker: "•".to_string(),
+                text: "nested again [1]".to_string(),
+            }
+        );
+        assert_eq!(
+            blocks[4],
+            Block::ListItem {
+                depth: 0,
+                marker: "2.".to_string(),
+                text: "second".to_string(),
+            }
+        );
+        assert_eq!(blocks[5], Block::Code(vec!["fn main() {}".to_string()]));
+        assert_eq!(citations.len(), 1);
+        assert_eq!(citations[0].url, "https://a.com");
+    }
+
+    #[test]
+    fn test_inline_text_links_and_emphasis() {
+        let mut citations = Vec::new();
+        assert_eq!(
+            inline_text("see [1] and [docs](https://d.io)", &mut citations),
+            "see [1] and docs [1]"
+        );
+        assert_eq!(citations[0].title, "docs");
+
+        assert_eq!(
+            strip_emphasis("**bold**, *it* and __strong__ text"),
+            "bold, it and strong text"
+        );
+        assert_eq!(strip_emphasis("2 * 3 * 4 = 24"), "2 * 3 * 4 = 24");
+        assert_eq!(
+            strip_emphasis("call `__init__` or foo__bar__baz"),
+            "call __init__ or foo__bar__baz"
+        );
+        assert_eq!(strip_emphasis("**a** **b**"), "a b");
+    }
+
+    #[test]
+    fn test_wrap_breaks_latin_and_cjk() {
+        let lines = wrap("alpha beta gamma delta", 10.0, 25.0, 0.55);
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|l| !l.starts_with(' ')));
+
+        let lines = wrap("新能源汽车市场趋势调研报告", 10.0, 20.0, 0.55);
+        assert!(lines.len() > 1);
+        assert_eq!(lines.concat(), "新能源汽车市场趋势调研报告");
+    }
+
+    #[test]
+    fn test_code_keeps_indentation() {
+        let (blocks, _) = parse_markdown("```rust\nfn main() {\n    x();\n}\n```");
+        let lines = vec![
+            "fn main() {".to_string(),
+            "    x();".to_string(),
+            "}".to_string(),
+        ];
+        assert_eq!(blocks[0], Block::Code(lines));
+
+        assert_eq!(wrap_code("    x();", 10.0, 100.0, 0.6), vec!["    x();"]);
+        let lines = wrap_code("    let value = compute(alpha, beta);", 10.0, 30.0, 0.6);
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|l| l.starts_with("    ") && l.len() > 4));
+        assert_eq!(
+            lines.iter().map(|l| &l[4..]).collect::<String>(),
+            "let value = compute(alpha, beta);"
+        );
+    }
+
+    #[test]
+    fn test_render_report_with_builtin_fonts() {
+        let todo = Todo {
+            id: "t1".to_string(),
+            title: "Market trends".to_string(),
+            description: Some("Quarterly overview".to_string()),
+            url: None,
+            status: TodoStatus::Review,
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            updated_at: "2024-01-01T00:00:00Z".to_string(),
+            tags: vec!["market".to_string()],
+            due_date: None,
+            deleted_at: None,
+            recurrence: None,
+            next_run_at: None,
+        };
+        let research = ResearchResult {
+            id: "r1".to_string(),
+            todo_id: "t1".to_string(),
+            source: "ChatGPT Deep Research".to_string(),
+            content: Some("## Summary\n\nSee [source](https://example.com).".to_string()),
+            raw_html: None,
+            started_at: Some("2024-01-01T00:00:00Z".to_string()),
+            completed_at: Some("2024-01-01T00:10:00Z".to_string()),
+            duration_seconds: Some(600),
+        };
+
+        let bytes = render_report_with_font(&todo, &research, None).unwrap();
+        assert!(bytes.starts_with(b"%PDF"));
+    }
+}
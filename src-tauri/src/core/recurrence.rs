@@ -0,0 +1,298 @@
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use croner::Cron;
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::core::{
+    error::AppError,
+    research,
+    todo::{self, Todo, TodoStatus},
+};
+
+/// How often due recurrences are checked; the finest cron granularity is one minute.
+const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// When a todo's research is re-run. Interval rules run at the local time of day (and
+/// weekday or day of month) of their `anchor`, which is filled in when the rule is set.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Recurrence {
+    Daily {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        anchor: Option<NaiveDateTime>,
+    },
+    Weekly {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        anchor: Option<NaiveDateTime>,
+    },
+    Monthly {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        anchor: Option<NaiveDateTime>,
+    },
+    /// Standard five-field cron expression, evaluated in local time.
+    Cron { expression: String },
+}
+
+impl Recurrence {
+    pub fn validate(&self) -> Result<(), AppError> {
+        if let Recurrence::Cron { expression } = self {
+            parse_cron(expression)?;
+        }
+        Ok(())
+    }
+
+    /// The rule with its anchor set to `at` (in local time) if it has none yet.
+    pub fn anchored(self, at: DateTime<Utc>) -> Self {
+        let local = || Some(at.with_timezone(&Local).naive_local());
+        match self {
+            Recurrence::Daily { anchor } => Recurrence::Daily {
+                anchor: anchor.or_else(local),
+            },
+            Recurrence::Weekly { anchor } => Recurrence::Weekly {
+                anchor: anchor.or_else(local),
+            },
+            Recurrence::Monthly { anchor } => Recurrence::Monthly {
+                anchor: anchor.or_else(local),
+            },
+            cron => cron,
+        }
+    }
+
+    /// The first run strictly after `now`. Interval rules are counted from their anchor
+    /// rather than the previous run, so a day clamped to the end of a short month does
+    /// not stick; a missed run fires once and is not replayed.
+    pub fn next_run(&self, now: DateTime<Utc>) -> Result<DateTime<Utc>, AppError> {
+        let anchor = match self {
+            Recurrence::Daily { anchor }
+            | Recurrence::Weekly { anchor }
+            | Recurrence::Monthly { anchor } => anchor,
+            Recurrence::Cron { expression } => {
+                return parse_cron(expression)?
+                    .find_next_occurrence(&now.with_timezone(&Local), false)
+                    .map(|next| next.with_timezone(&Utc))
+                    .map_err(|e| {
+                        AppError::Validation(format!("Cron expression has no next run: {}", e))
+                    });
+            }
+        };
+
+        let local_now = now.with_timezone(&Local).naive_local();
+        let anchor = anchor.unwrap_or(local_now);
+        let days = (local_now.date() - anchor.date()).num_days();
+        let months = (local_now.year() - anchor.year()) as i64 * 12 + local_now.month() as i64
+            - anchor.month() as i64;
+        // Start just before the period holding `now` and walk forward from there.
+        let estimate = match self {
+            Recurrence::Weekly { .. } => days / 7,
+            Recurrence::Monthly { .. } => months,
+            _ => days,
+        };
+
+        let mut n = (estimate - 1).max(1);
+        loop {
+            let date = match self {
+                Recurrence::Daily { .. } => anchor.date().checked_add_signed(Duration::days(n)),
+                Recurrence::Weekly { .. } => anchor.date().checked_add_signed(Duration::weeks(n)),
+                _ => add_months_clamped(anchor.date(), n),
+            };
+            let next = date
+                .and_then(|date| to_utc(date.and_time(anchor.time())))
+                .ok_or_else(|| AppError::Validation("Recurrence is out of range".to_string()))?;
+            if next > now {
+                return Ok(next);
+            }
+            n += 1;
+        }
+    }
+}
+
+/// `date` moved by `months`, keeping its day of month or the last day of shorter months.
+fn add_months_clamped(date: NaiveDate, months: i64) -> Option<NaiveDate> {
+    let index = date.year() as i64 * 12 + date.month0() as i64 + months;
+    let (year, month) = (
+        i32::try_from(index.div_euclid(12)).ok()?,
+        index.rem_euclid(12) as u32 + 1,
+    );
+    (1..=date.day())
+        .rev()
+        .find_map(|day| NaiveDate::from_ymd_opt(year, month, day))
+}
+
+/// Resolves a local wall-clock time. An ambiguous time takes its first occurrence, and a
+/// time skipped by a DST change runs an hour later.
+fn to_utc(local: NaiveDateTime) -> Option<DateTime<Utc>> {
+    Local
+        .from_local_datetime(&local)
+        .earliest()
+        .or_else(|| {
+            Local
+                .from_local_datetime(&(local + Duration::hours(1)))
+                .earliest()
+        })
+        .map(|at| at.with_timezone(&Utc))
+}
+
+fn parse_cron(expression: &str) -> Result<Cron, AppError> {
+    Cron::new(expression).parse().map_err(|e| {
+        AppError::Validation(format!("Invalid cron expression '{}': {}", expression, e))
+    })
+}
+
+/// Sets or clears the recurrence of a todo and schedules its first run.
+pub fn set_recurrence(id: &str, recurrence: Option<Recurrence>) -> Result<Option<Todo>, AppError> {
+    let now = Utc::now();
+    let recurrence = recurrence.map(|rule| rule.anchored(now));
+    let next_run_at = match &recurrence {
+        Some(rule) => {
+            rule.validate()?;
+            Some(rule.next_run(now)?.to_rfc3339())
+        }
+        None => None,
+    };
+    Ok(todo::set_recurrence(id, recurrence, next_run_at)?)
+}
+
+/// Starts research for every todo whose next run has passed, then schedules the run after.
+pub fn run_due(app: &AppHandle) -> Result<usize> {
+    let now = Utc::now();
+    let mut started = 0;
+
+    for t in todo::get_due_recurring(now)? {
+        let Some(rule) = t.recurrence.clone() else {
+            continue;
+        };
+
+        // Reschedule first, so a failing start is retried at the next run rather than
+        // every minute. Rules stored before anchors existed are anchored on this run.
+        let previous = t
+            .next_run_at
+            .as_deref()
+            .and_then(|at| at.parse::<DateTime<Utc>>().ok());
+        let rule = rule.anchored(previous.unwrap_or(now));
+        let next_run_at = match rule.next_run(now) {
+            Ok(next) => Some(next.to_rfc3339()),
+            Err(e) => {
+                error!("[recurrence] Disabling schedule of {}: {}", t.id, e);
+                None
+            }
+        };
+        let recurrence = next_run_at.as_ref().map(|_| rule);
+        todo::set_recurrence(&t.id, recurrence, next_run_at)?;
+
+        if t.status == TodoStatus::Researching {
+            info!(
+                "[recurrence] {} is still researching, skipping this run",
+                t.id
+            );
+            continue;
+        }
+
+        match research::begin(app, &t.id) {
+            Ok(_) => {
+                info!("[recurrence] Started scheduled research for '{}'", t.title);
+                started += 1;
+            }
+            Err(e) => error!("[recurrence] Failed to start research for {}: {}", t.id, e),
+        }
+    }
+
+    Ok(started)
+}
+
+/// Checks for due recurring todos every minute.
+pub fn start(app: &AppHandle) {
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Err(e) = run_due(&handle) {
+                error!("[recurrence] {}", e);
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    fn local(s: &str) -> NaiveDateTime {
+        s.parse().unwrap()
+    }
+
+    fn at_local(s: &str) -> DateTime<Utc> {
+        to_utc(local(s)).unwrap()
+    }
+
+    #[test]
+    fn test_interval_rules_keep_time_of_day() {
+        let now = at_local("2024-03-10T09:00:00");
+        let daily = Recurrence::Daily { anchor: None }.anchored(now);
+        assert_eq!(
+            daily.next_run(now).unwrap(),
+            at_local("2024-03-11T09:00:00")
+        );
+
+        // A run missed while the app was closed fires once, then resumes the cadence.
+        let weekly = Recurrence::Weekly {
+            anchor: Some(local("2024-03-01T06:30:00")),
+        };
+        assert_eq!(
+            weekly.next_run(now).unwrap(),
+            at_local("2024-03-15T06:30:00")
+        );
+
+        // Months are counted from the anchor, so the clamp to Feb 29 does not stick.
+        let monthly = Recurrence::Monthly {
+            anchor: Some(local("2024-01-31T08:00:00")),
+        };
+        assert_eq!(
+            monthly.next_run(at_local("2024-02-01T00:00:00")).unwrap(),
+            at_local("2024-02-29T08:00:00")
+        );
+        assert_eq!(
+            monthly.next_run(now).unwrap(),
+            at_local("2024-03-31T08:00:00")
+        );
+        assert_eq!(
+            monthly.next_run(at_local("2024-03-31T08:00:00")).unwrap(),
+            at_local("2024-04-30T08:00:00")
+        );
+    }
+
+    #[test]
+    fn test_rules_without_anchor_round_trip() {
+        let rule: Recurrence = serde_json::from_str(r#"{"kind": "monthly"}"#).unwrap();
+        assert_eq!(rule, Recurrence::Monthly { anchor: None });
+
+        let anchored = rule.anchored(at_local("2024-01-31T08:00:00"));
+        assert_eq!(
+            serde_json::to_string(&anchored).unwrap(),
+            r#"{"kind":"monthly","anchor":"2024-01-31T08:00:00"}"#
+        );
+    }
+
+    #[test]
+    fn test_cron_rules() {
+        let rule = Recurrence::Cron {
+            expression: "0 9 1 * *".to_string(),
+        };
+        assert!(rule.validate().is_ok());
+
+        let now = Utc::now();
+        let next = rule.next_run(now).unwrap();
+        assert!(next > now);
+        assert!(next - now <= Duration::days(31));
+
+        let invalid = Recurrence::Cron {
+            expression: "every month".to_string(),
+        };
+        assert_eq!(invalid.validate().unwrap_err().code(), "Validation");
+    }
+}
@@ -0,0 +1,426 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone, Utc};
+use log::{error, info};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::core::{
+    db,
+    error::AppError,
+    notify::{self, Notice, NoticeKind},
+};
+
+/// Minutes before the due time to notify at, used until a todo sets its own offsets:
+/// one day ahead and when it falls due.
+pub const DEFAULT_OFFSETS: [u32; 2] = [24 * 60, 0];
+
+/// Longest reminder offset, in minutes (30 days).
+const MAX_OFFSET: u32 = 30 * 24 * 60;
+
+/// Date-only due dates fall due at this local hour.
+const DUE_HOUR: u32 = 9;
+
+/// How often reminders are checked after the startup catch-up.
+const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Reminder configuration of one todo.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReminderSettings {
+    pub todo_id: String,
+    /// Minutes before the due time, largest first.
+    pub offsets: Vec<u32>,
+    /// Whether `offsets` are the defaults rather than set on the todo.
+    pub is_default: bool,
+    pub snoozed_until: Option<String>,
+}
+
+/// A notification to show for a todo.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reminder {
+    pub todo_id: String,
+    pub title: String,
+    pub due_at: DateTime<Utc>,
+    pub overdue: bool,
+}
+
+/// When a due date falls due: RFC 3339 values as stored, date-only values at
+/// [`DUE_HOUR`] local time.
+pub fn due_at(due_date: &str) -> Option<DateTime<Utc>> {
+    if let Ok(date) = NaiveDate::parse_from_str(due_date, "%Y-%m-%d") {
+        let local = Local
+            .from_local_datetime(&date.and_hms_opt(DUE_HOUR, 0, 0)?)
+            .earliest()?;
+        return Some(local.with_timezone(&Utc));
+    }
+    DateTime::parse_from_rfc3339(due_date)
+        .ok()
+        .map(|at| at.with_timezone(&Utc))
+}
+
+/// A todo with a due date, joined with its reminder settings.
+struct Candidate {
+    todo_id: String,
+    title: String,
+    due_date: String,
+    offsets: Option<String>,
+    snoozed_until: Option<String>,
+}
+
+fn parse_offsets(value: Option<String>) -> Option<Vec<u32>> {
+    value.and_then(|json| serde_json::from_str(&json).ok())
+}
+
+fn ensure_todo(conn: &Connection, todo_id: &str) -> Result<(), AppError> {
+    let exists = conn
+        .query_row(
+            "SELECT 1 FROM todos WHERE id = ?1 AND deleted_at IS NULL",
+            [todo_id],
+            |_| Ok(()),
+        )
+        .optional()?;
+    exists.ok_or_else(|| AppError::NotFound(format!("Todo not found: {}", todo_id)))
+}
+
+pub fn get_settings(conn: &Connection, todo_id: &str) -> Result<ReminderSettings, AppError> {
+    ensure_todo(conn, todo_id)?;
+    let row: Option<(Option<String>, Option<String>)> = conn
+        .query_row(
+            "SELECT offsets, snoozed_until FROM reminders WHERE todo_id = ?1",
+            [todo_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    let (offsets, snoozed_until) = row.unwrap_or_default();
+    let offsets = parse_offsets(offsets);
+
+    Ok(ReminderSettings {
+        todo_id: todo_id.to_string(),
+        is_default: offsets.is_none(),
+        offsets: offsets.unwrap_or_else(|| DEFAULT_OFFSETS.to_vec()),
+        snoozed_until,
+    })
+}
+
+/// Sets the reminder offsets of a todo; `None` goes back to [`DEFAULT_OFFSETS`] and an
+/// empty list turns its reminders off.
+pub fn set_offsets(
+    conn: &Connection,
+    todo_id: &str,
+    offsets: Option<Vec<u32>>,
+) -> Result<ReminderSettings, AppError> {
+    ensure_todo(conn, todo_id)?;
+    let offsets = match offsets {
+        Some(mut offsets) => {
+            if let Some(offset) = offsets.iter().find(|o| **o > MAX_OFFSET) {
+                return Err(AppError::Validation(format!(
+                    "Reminder offset of {} minutes is longer than 30 days",
+                    offset
+                )));
+            }
+            offsets.sort_unstable_by(|a, b| b.cmp(a));
+            offsets.dedup();
+            Some(serde_json::to_string(&offsets)?)
+        }
+        None => None,
+    };
+
+    conn.execute(
+        "INSERT INTO reminders (todo_id, offsets) VALUES (?1, ?2)
+         ON CONFLICT(todo_id) DO UPDATE SET offsets = excluded.offsets",
+        params![todo_id, offsets],
+    )?;
+    get_settings(conn, todo_id)
+}
+
+/// Silences a todo's reminders for `minutes`, then notifies once more.
+pub fn snooze(
+    conn: &Connection,
+    todo_id: &str,
+    minutes: u32,
+    now: DateTime<Utc>,
+) -> Result<ReminderSettings, AppError> {
+    ensure_todo(conn, todo_id)?;
+    if minutes == 0 {
+        return Err(AppError::Validation(
+            "Snooze must last at least one minute".to_string(),
+        ));
+    }
+
+    let until = (now + Duration::minutes(minutes as i64)).to_rfc3339();
+    conn.execute(
+        "INSERT INTO reminders (todo_id, snoozed_until) VALUES (?1, ?2)
+         ON CONFLICT(todo_id) DO UPDATE SET snoozed_until = excluded.snoozed_until",
+        params![todo_id, until],
+    )?;
+    get_settings(conn, todo_id)
+}
+
+/// Finds the reminders to show at `now` and records them as sent in one transaction, so a
+/// restart never notifies twice. Offsets missed while the app was closed are caught up
+/// with a single notification per todo, and an expired snooze notifies once more.
+pub fn collect_due(conn: &mut Connection, now: DateTime<Utc>) -> Result<Vec<Reminder>> {
+    let tx = conn.transaction()?;
+    let now_str = now.to_rfc3339();
+
+    // Moving a due date re-arms its reminders.
+    tx.execute(
+        "DELETE FROM reminder_log WHERE NOT EXISTS (
+             SELECT 1 FROM todos t
+             WHERE t.id = reminder_log.todo_id AND t.due_date = reminder_log.due_date
+         )",
+        [],
+    )?;
+
+    let candidates: Vec<Candidate> = {
+        let mut stmt = tx.prepare(
+            "SELECT t.id, t.title, t.due_date, r.offsets, r.snoozed_until
+             FROM todos t LEFT JOIN reminders r ON r.todo_id = t.id
+             WHERE t.due_date IS NOT NULL AND t.deleted_at IS NULL
+               AND t.status NOT IN ('done', 'archived')",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(Candidate {
+                todo_id: row.get(0)?,
+                title: row.get(1)?,
+                due_date: row.get(2)?,
+                offsets: row.get(3)?,
+                snoozed_until: row.get(4)?,
+            })
+        })?;
+        rows.collect::<rusqlite::Result<_>>()?
+    };
+
+    let mut reminders = Vec::new();
+    for Candidate {
+        todo_id,
+        title,
+        due_date,
+        offsets,
+        snoozed_until,
+    } in candidates
+    {
+        let Some(due) = due_at(&due_date) else {
+            continue;
+        };
+        let snoozed = snoozed_until
+            .as_deref()
+            .and_then(|at| at.parse::<DateTime<Utc>>().ok());
+        if snoozed.is_some_and(|until| until > now) {
+            continue;
+        }
+
+        let sent: Vec<u32> = {
+            let mut stmt = tx.prepare(
+                "SELECT offset_minutes FROM reminder_log WHERE todo_id = ?1 AND due_date = ?2",
+            )?;
+            let rows = stmt.query_map(params![todo_id, due_date], |row| row.get(0))?;
+            rows.collect::<rusqlite::Result<_>>()?
+        };
+        let unsent: Vec<u32> = parse_offsets(offsets)
+            .unwrap_or_else(|| DEFAULT_OFFSETS.to_vec())
+            .into_iter()
+            .filter(|o| due - Duration::minutes(*o as i64) <= now && !sent.contains(o))
+            .collect();
+
+        for offset in &unsent {
+            tx.execute(
+                "INSERT INTO reminder_log (todo_id, due_date, offset_minutes, notified_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![todo_id, due_date, offset, now_str],
+            )?;
+        }
+        if snoozed.is_some() {
+            tx.execute(
+                "UPDATE reminders SET snoozed_until = NULL WHERE todo_id = ?1",
+                [&todo_id],
+            )?;
+        } else if unsent.is_empty() {
+            continue;
+        }
+
+        reminders.push(Reminder {
+            todo_id,
+            title,
+            due_at: due,
+            overdue: due <= now,
+        });
+    }
+
+    tx.commit()?;
+    Ok(reminders)
+}
+
+fn to_notice(reminder: &Reminder) -> Notice {
+    let due = reminder
+        .due_at
+        .with_timezone(&Local)
+        .format("%Y-%m-%d %H:%M");
+    let (title, body) = if reminder.overdue {
+        (
+            "待办已逾期",
+            format!("\"{}\" 已于 {} 到期", reminder.title, due),
+        )
+    } else {
+        (
+            "待办即将到期",
+            format!("\"{}\" 将于 {} 到期", reminder.title, due),
+        )
+    };
+
+    Notice {
+        kind: NoticeKind::Reminder,
+        todo_id: Some(reminder.todo_id.clone()),
+        title: title.to_string(),
+        body,
+    }
+}
+
+/// Shows every reminder that is due now, as one summary when there are several.
+pub fn run_due(app: &AppHandle) -> Result<usize> {
+    let reminders = collect_due(&mut db::writer(), Utc::now())?;
+    if reminders.is_empty() {
+        return Ok(0);
+    }
+
+    let prefs = notify::load_prefs();
+    for reminder in &reminders {
+        info!(
+            "[reminders] Reminding about '{}' ({})",
+            reminder.title, reminder.todo_id
+        );
+        notify::push(&prefs, to_notice(reminder));
+    }
+    notify::flush(app, &prefs);
+    Ok(reminders.len())
+}
+
+/// Catches up on reminders missed while the app was closed, then checks every minute.
+pub fn start(app: &AppHandle) {
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Err(e) = run_due(&handle) {
+                error!("[reminders] {}", e);
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(include_str!("../migrations/001_init.sql"))
+            .unwrap();
+        conn
+    }
+
+    fn seed(conn: &Connection, id: &str, due_date: &str) {
+        conn.execute(
+            "INSERT INTO todos (id, title, due_date) VALUES (?1, ?1, ?2)",
+            params![id, due_date],
+        )
+        .unwrap();
+    }
+
+    fn at(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    fn ids(reminders: &[Reminder]) -> Vec<&str> {
+        reminders.iter().map(|r| r.todo_id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_reminders_fire_once_per_offset() {
+        let mut conn = open_db();
+        seed(&conn, "soon", "2024-05-02T09:00:00Z");
+        seed(&conn, "later", "2024-05-10T09:00:00Z");
+
+        // One day ahead.
+        let reminders = collect_due(&mut conn, at("2024-05-01T09:30:00Z")).unwrap();
+        assert_eq!(ids(&reminders), vec!["soon"]);
+        assert!(!reminders[0].overdue);
+        assert!(collect_due(&mut conn, at("2024-05-01T10:00:00Z"))
+            .unwrap()
+            .is_empty());
+
+        // Due.
+        let reminders = collect_due(&mut conn, at("2024-05-02T09:00:00Z")).unwrap();
+        assert_eq!(ids(&reminders), vec!["soon"]);
+        assert!(reminders[0].overdue);
+        assert!(collect_due(&mut conn, at("2024-05-03T09:00:00Z"))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_missed_reminders_catch_up_once() {
+        let mut conn = open_db();
+        seed(&conn, "missed", "2024-05-02T09:00:00Z");
+        conn.execute("UPDATE todos SET status = 'done' WHERE id = 'missed'", [])
+            .unwrap();
+        seed(&conn, "overdue", "2024-05-02T09:00:00Z");
+
+        let reminders = collect_due(&mut conn, at("2024-05-20T08:00:00Z")).unwrap();
+        assert_eq!(ids(&reminders), vec!["overdue"]);
+        assert!(collect_due(&mut conn, at("2024-05-20T08:01:00Z"))
+            .unwrap()
+            .is_empty());
+
+        // Moving the due date re-arms the reminders.
+        conn.execute(
+            "UPDATE todos SET due_date = '2024-05-21T09:00:00Z' WHERE id = 'overdue'",
+            [],
+        )
+        .unwrap();
+        let reminders = collect_due(&mut conn, at("2024-05-20T10:00:00Z")).unwrap();
+        assert_eq!(ids(&reminders), vec!["overdue"]);
+    }
+
+    #[test]
+    fn test_offsets_and_snooze() {
+        let mut conn = open_db();
+        seed(&conn, "todo", "2024-05-02T09:00:00Z");
+        assert!(get_settings(&conn, "todo").unwrap().is_default);
+
+        let settings = set_offsets(&conn, "todo", Some(vec![0, 60, 60])).unwrap();
+        assert_eq!(settings.offsets, vec![60, 0]);
+        assert!(collect_due(&mut conn, at("2024-05-01T09:30:00Z"))
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            collect_due(&mut conn, at("2024-05-02T08:00:00Z"))
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // Snoozing holds back the due reminder, then notifies once when it expires.
+        snooze(&conn, "todo", 30, at("2024-05-02T08:50:00Z")).unwrap();
+        assert!(collect_due(&mut conn, at("2024-05-02T09:00:00Z"))
+            .unwrap()
+            .is_empty());
+        let reminders = collect_due(&mut conn, at("2024-05-02T09:20:00Z")).unwrap();
+        assert_eq!(ids(&reminders), vec!["todo"]);
+        assert!(get_settings(&conn, "todo").unwrap().snoozed_until.is_none());
+        assert!(collect_due(&mut conn, at("2024-05-02T09:30:00Z"))
+            .unwrap()
+            .is_empty());
+
+        assert_eq!(
+            set_offsets(&conn, "todo", Some(vec![MAX_OFFSET + 1]))
+                .unwrap_err()
+                .code(),
+            "Validation"
+        );
+        assert_eq!(
+            snooze(&conn, "missing", 10, Utc::now()).unwrap_err().code(),
+            "NotFound"
+        );
+    }
+}
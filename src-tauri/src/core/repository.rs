@@ -0,0 +1,887 @@
+use chrono::{DateTime, Utc};
+use rusqlite::{ffi, params, Connection, Result as SqliteResult};
+use std::sync::Mutex;
+use uuid::Uuid;
+
+use crate::core::{
+    recurrence::Recurrence,
+    todo::{
+        recurrence_to_json, row_to_todo, tags_to_json, CreateTodoInput, ResearchResult,
+        StatusCounts, Todo, TodoStatus, UpdateTodoInput, TODO_COLUMNS,
+    },
+};
+
+/// Storage for todos and their research results. Trashed todos are invisible to everything
+/// except the trash methods.
+pub trait TodoRepository {
+    fn create(&self, input: CreateTodoInput) -> SqliteResult<Todo>;
+    /// Todos with the given status, or every non-archived todo; newest first.
+    fn list(&self, status: Option<&str>) -> SqliteResult<Vec<Todo>>;
+    fn get(&self, id: &str) -> SqliteResult<Option<Todo>>;
+    fn update(&self, id: &str, input: UpdateTodoInput) -> SqliteResult<Option<Todo>>;
+    /// Moves the todo to the trash.
+    fn delete(&self, id: &str) -> SqliteResult<bool>;
+    /// Trashed todos, most recently deleted first.
+    fn list_trash(&self) -> SqliteResult<Vec<Todo>>;
+    fn restore(&self, id: &str) -> SqliteResult<Option<Todo>>;
+    /// Permanently deletes a trashed todo and its research results.
+    fn purge(&self, id: &str) -> SqliteResult<bool>;
+    /// Permanently deletes todos trashed before `cutoff` (RFC 3339) and returns how many.
+    fn purge_deleted_before(&self, cutoff: &str) -> SqliteResult<usize>;
+    fn status_counts(&self) -> SqliteResult<StatusCounts>;
+    /// Stores a research result and moves the todo to review. `converter_version` is the
+    /// version of the HTML converter that produced `content`, if it came from `raw_html`.
+    fn save_research(
+        &self,
+        todo_id: &str,
+        source: &str,
+        content: &str,
+        raw_html: Option<&str>,
+        converter_version: Option<i64>,
+        started_at: &str,
+    ) -> SqliteResult<ResearchResult>;
+    fn latest_research(&self, todo_id: &str) -> SqliteResult<Option<ResearchResult>>;
+    /// Every research result of a todo, newest first.
+    fn list_research(&self, todo_id: &str) -> SqliteResult<Vec<ResearchResult>>;
+    /// Sets or clears the recurrence together with its next run time.
+    fn set_recurrence(
+        &self,
+        id: &str,
+        recurrence: Option<Recurrence>,
+        next_run_at: Option<String>,
+    ) -> SqliteResult<Option<Todo>>;
+    /// Recurring todos whose next run is at or before `now` (RFC 3339), skipping archived
+    /// and trashed ones.
+    fn due_recurring(&self, now: &str) -> SqliteResult<Vec<Todo>>;
+}
+
+/// Repository over a single SQLite connection, either the writer or a pooled reader.
+pub struct SqliteTodoRepository<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> SqliteTodoRepository<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        Self { conn }
+    }
+}
+
+impl TodoRepository for SqliteTodoRepository<'_> {
+    fn create(&self, input: CreateTodoInput) -> SqliteResult<Todo> {
+        let todo = new_todo(input);
+
+        self.conn.execute(
+            "INSERT INTO todos (id, title, description, url, status, created_at, updated_at, tags, due_date)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                todo.id,
+                todo.title,
+                todo.description,
+                todo.url,
+                todo.status.as_str(),
+                todo.created_at,
+                todo.updated_at,
+                tags_to_json(&todo.tags),
+                todo.due_date
+            ],
+        )?;
+
+        Ok(todo)
+    }
+
+    fn list(&self, status: Option<&str>) -> SqliteResult<Vec<Todo>> {
+        let sql = match status {
+            Some(_) => format!(
+                "SELECT {} FROM todos WHERE status = ?1 AND deleted_at IS NULL
+                 ORDER BY created_at DESC",
+                TODO_COLUMNS
+            ),
+            None => format!(
+                "SELECT {} FROM todos WHERE status != 'archived' AND deleted_at IS NULL
+                 ORDER BY created_at DESC",
+                TODO_COLUMNS
+            ),
+        };
+
+        let mut stmt = self.conn.prepare(&sql)?;
+
+        let rows = if let Some(status) = status {
+            stmt.query_map([status], row_to_todo)?
+        } else {
+            stmt.query_map([], row_to_todo)?
+        };
+
+        rows.collect()
+    }
+
+    fn get(&self, id: &str) -> SqliteResult<Option<Todo>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM todos WHERE id = ?1 AND deleted_at IS NULL",
+            TODO_COLUMNS
+        ))?;
+
+        let mut rows = stmt.query([id])?;
+        match rows.next()? {
+            Some(row) => Ok(Some(row_to_todo(row)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn update(&self, id: &str, input: UpdateTodoInput) -> SqliteResult<Option<Todo>> {
+        let Some(current) = self.get(id)? else {
+            return Ok(None);
+        };
+        let todo = apply_update(current, input);
+
+        self.conn.execute(
+            "UPDATE todos SET title = ?1, description = ?2, url = ?3, status = ?4, updated_at = ?5,
+             tags = ?6, due_date = ?7 WHERE id = ?8",
+            params![
+                todo.title,
+                todo.description,
+                todo.url,
+                todo.status.as_str(),
+                todo.updated_at,
+                tags_to_json(&todo.tags),
+                todo.due_date,
+                id
+            ],
+        )?;
+
+        Ok(Some(todo))
+    }
+
+    fn delete(&self, id: &str) -> SqliteResult<bool> {
+        let affected = self.conn.execute(
+            "UPDATE todos SET deleted_at = ?1 WHERE id = ?2 AND deleted_at IS NULL",
+            params![Utc::now().to_rfc3339(), id],
+        )?;
+        Ok(affected > 0)
+    }
+
+    fn list_trash(&self) -> SqliteResult<Vec<Todo>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM todos WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC",
+            TODO_COLUMNS
+        ))?;
+        let rows = stmt.query_map([], row_to_todo)?;
+        rows.collect()
+    }
+
+    fn restore(&self, id: &str) -> SqliteResult<Option<Todo>> {
+        let affected = self.conn.execute(
+            "UPDATE todos SET deleted_at = NULL, updated_at = ?1
+             WHERE id = ?2 AND deleted_at IS NOT NULL",
+            params![Utc::now().to_rfc3339(), id],
+        )?;
+        if affected == 0 {
+            return Ok(None);
+        }
+        self.get(id)
+    }
+
+    fn purge(&self, id: &str) -> SqliteResult<bool> {
+        let affected = self.conn.execute(
+            "DELETE FROM todos WHERE id = ?1 AND deleted_at IS NOT NULL",
+            [id],
+        )?;
+        Ok(affected > 0)
+    }
+
+    fn purge_deleted_before(&self, cutoff: &str) -> SqliteResult<usize> {
+        self.conn.execute(
+            "DELETE FROM todos WHERE deleted_at IS NOT NULL AND deleted_at < ?1",
+            [cutoff],
+        )
+    }
+
+    fn status_counts(&self) -> SqliteResult<StatusCounts> {
+        let mut stmt = self.conn.prepare(
+            "SELECT status, COUNT(*) as count FROM todos WHERE deleted_at IS NULL GROUP BY status",
+        )?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+        })?;
+
+        let mut counts = StatusCounts::default();
+        for row in rows {
+            let (status, count) = row?;
+            counts.add(&status, count);
+        }
+
+        Ok(counts)
+    }
+
+    fn save_research(
+        &self,
+        todo_id: &str,
+        source: &str,
+        content: &str,
+        raw_html: Option<&str>,
+        converter_version: Option<i64>,
+        started_at: &str,
+    ) -> SqliteResult<ResearchResult> {
+        let result = new_research(todo_id, source, content, raw_html, started_at);
+
+        let tx = self.conn.unchecked_transaction()?;
+
+        tx.execute(
+            "INSERT INTO research_results (id, todo_id, source, content, raw_html, started_at, completed_at, duration_seconds, converter_version)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                result.id,
+                result.todo_id,
+                result.source,
+                result.content,
+                result.raw_html,
+                result.started_at,
+                result.completed_at,
+                result.duration_seconds,
+                converter_version
+            ],
+        )?;
+
+        tx.execute(
+            "UPDATE todos SET status = 'review', updated_at = ?1 WHERE id = ?2",
+            params![result.completed_at, todo_id],
+        )?;
+
+        tx.commit()?;
+
+        Ok(result)
+    }
+
+    fn latest_research(&self, todo_id: &str) -> SqliteResult<Option<ResearchResult>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM research_results WHERE todo_id = ?1 ORDER BY completed_at DESC LIMIT 1",
+            RESEARCH_COLUMNS
+        ))?;
+
+        let mut rows = stmt.query([todo_id])?;
+        match rows.next()? {
+            Some(row) => Ok(Some(row_to_research(row)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn list_research(&self, todo_id: &str) -> SqliteResult<Vec<ResearchResult>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM research_results WHERE todo_id = ?1 ORDER BY completed_at DESC",
+            RESEARCH_COLUMNS
+        ))?;
+        let rows = stmt.query_map([todo_id], row_to_research)?;
+        rows.collect()
+    }
+
+    fn set_recurrence(
+        &self,
+        id: &str,
+        recurrence: Option<Recurrence>,
+        next_run_at: Option<String>,
+    ) -> SqliteResult<Option<Todo>> {
+        let affected = self.conn.execute(
+            "UPDATE todos SET recurrence = ?1, next_run_at = ?2 WHERE id = ?3 AND deleted_at IS NULL",
+            params![recurrence_to_json(recurrence.as_ref()), next_run_at, id],
+        )?;
+        if affected == 0 {
+            return Ok(None);
+        }
+        self.get(id)
+    }
+
+    fn due_recurring(&self, now: &str) -> SqliteResult<Vec<Todo>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM todos
+             WHERE recurrence IS NOT NULL AND deleted_at IS NULL AND status != 'archived'
+               AND julianday(next_run_at) <= julianday(?1)
+             ORDER BY next_run_at",
+            TODO_COLUMNS
+        ))?;
+        let rows = stmt.query_map([now], row_to_todo)?;
+        rows.collect()
+    }
+}
+
+const RESEARCH_COLUMNS: &str =
+    "id, todo_id, source, content, raw_html, started_at, completed_at, duration_seconds";
+
+fn row_to_research(row: &rusqlite::Row) -> SqliteResult<ResearchResult> {
+    Ok(ResearchResult {
+        id: row.get(0)?,
+        todo_id: row.get(1)?,
+        source: row.get(2)?,
+        content: row.get(3)?,
+        raw_html: row.get(4)?,
+        started_at: row.get(5)?,
+        completed_at: row.get(6)?,
+        duration_seconds: row.get(7)?,
+    })
+}
+
+/// Repository kept entirely in memory, for tests and previews that must not touch disk.
+#[cfg_attr(not(test), allow(dead_code))]
+#[derive(Default)]
+pub struct MemoryTodoRepository {
+    todos: Mutex<Vec<Todo>>,
+    research: Mutex<Vec<ResearchResult>>,
+}
+
+#[cfg_attr(not(test), allow(dead_code))]
+impl MemoryTodoRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Removes matching todos and, mirroring ON DELETE CASCADE, their research results.
+    fn purge_where(&self, matches: impl Fn(&Todo) -> bool) -> usize {
+        let mut todos = self.todos.lock().unwrap();
+        let (purged, kept): (Vec<Todo>, Vec<Todo>) = todos.drain(..).partition(|t| matches(t));
+        *todos = kept;
+
+        self.research
+            .lock()
+            .unwrap()
+            .retain(|r| !purged.iter().any(|t| t.id == r.todo_id));
+        purged.len()
+    }
+}
+
+impl TodoRepository for MemoryTodoRepository {
+    fn create(&self, input: CreateTodoInput) -> SqliteResult<Todo> {
+        let todo = new_todo(input);
+        self.todos.lock().unwrap().push(todo.clone());
+        Ok(todo)
+    }
+
+    fn list(&self, status: Option<&str>) -> SqliteResult<Vec<Todo>> {
+        let mut todos: Vec<Todo> = self
+            .todos
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|t| t.deleted_at.is_none())
+            .filter(|t| match status {
+                Some(status) => t.status.as_str() == status,
+                None => t.status != TodoStatus::Archived,
+            })
+            .cloned()
+            .collect();
+        todos.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(todos)
+    }
+
+    fn get(&self, id: &str) -> SqliteResult<Option<Todo>> {
+        Ok(self
+            .todos
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|t| t.id == id && t.deleted_at.is_none())
+            .cloned())
+    }
+
+    fn update(&self, id: &str, input: UpdateTodoInput) -> SqliteResult<Option<Todo>> {
+        let mut todos = self.todos.lock().unwrap();
+        let Some(slot) = todos
+            .iter_mut()
+            .find(|t| t.id == id && t.deleted_at.is_none())
+        else {
+            return Ok(None);
+        };
+        *slot = apply_update(slot.clone(), input);
+        Ok(Some(slot.clone()))
+    }
+
+    fn delete(&self, id: &str) -> SqliteResult<bool> {
+        let mut todos = self.todos.lock().unwrap();
+        match todos
+            .iter_mut()
+            .find(|t| t.id == id && t.deleted_at.is_none())
+        {
+            Some(todo) => {
+                todo.deleted_at = Some(Utc::now().to_rfc3339());
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn list_trash(&self) -> SqliteResult<Vec<Todo>> {
+        let mut todos: Vec<Todo> = self
+            .todos
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|t| t.deleted_at.is_some())
+            .cloned()
+            .collect();
+        todos.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at));
+        Ok(todos)
+    }
+
+    fn restore(&self, id: &str) -> SqliteResult<Option<Todo>> {
+        let mut todos = self.todos.lock().unwrap();
+        let Some(todo) = todos
+            .iter_mut()
+            .find(|t| t.id == id && t.deleted_at.is_some())
+        else {
+            return Ok(None);
+        };
+        todo.deleted_at = None;
+        todo.updated_at = Utc::now().to_rfc3339();
+        Ok(Some(todo.clone()))
+    }
+
+    fn purge(&self, id: &str) -> SqliteResult<bool> {
+        Ok(self.purge_where(|t| t.id == id && t.deleted_at.is_some()) > 0)
+    }
+
+    fn purge_deleted_before(&self, cutoff: &str) -> SqliteResult<usize> {
+        Ok(self.purge_where(|t| t.deleted_at.as_deref().is_some_and(|d| d < cutoff)))
+    }
+
+    fn status_counts(&self) -> SqliteResult<StatusCounts> {
+        let mut counts = StatusCounts::default();
+        for todo in self.todos.lock().unwrap().iter() {
+            if todo.deleted_at.is_none() {
+                counts.add(todo.status.as_str(), 1);
+            }
+        }
+        Ok(counts)
+    }
+
+    fn save_research(
+        &self,
+        todo_id: &str,
+        source: &str,
+        content: &str,
+        raw_html: Option<&str>,
+        _converter_version: Option<i64>,
+        started_at: &str,
+    ) -> SqliteResult<ResearchResult> {
+        let result = new_research(todo_id, source, content, raw_html, started_at);
+
+        let mut todos = self.todos.lock().unwrap();
+        let Some(todo) = todos.iter_mut().find(|t| t.id == todo_id) else {
+            // Same failure SQLite reports for the todo_id foreign key.
+            return Err(rusqlite::Error::SqliteFailure(
+                ffi::Error::new(ffi::SQLITE_CONSTRAINT_FOREIGNKEY),
+                Some("FOREIGN KEY constraint failed".to_string()),
+            ));
+        };
+        todo.status = TodoStatus::Review;
+        todo.updated_at = result.completed_at.clone().unwrap_or_default();
+
+        self.research.lock().unwrap().push(result.clone());
+        Ok(result)
+    }
+
+    fn latest_research(&self, todo_id: &str) -> SqliteResult<Option<ResearchResult>> {
+        Ok(self
+            .research
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|r| r.todo_id == todo_id)
+            .max_by(|a, b| a.completed_at.cmp(&b.completed_at))
+            .cloned())
+    }
+
+    fn list_research(&self, todo_id: &str) -> SqliteResult<Vec<ResearchResult>> {
+        let mut results: Vec<ResearchResult> = self
+            .research
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|r| r.todo_id == todo_id)
+            .cloned()
+            .collect();
+        results.sort_by(|a, b| b.completed_at.cmp(&a.completed_at));
+        Ok(results)
+    }
+
+    fn set_recurrence(
+        &self,
+        id: &str,
+        recurrence: Option<Recurrence>,
+        next_run_at: Option<String>,
+    ) -> SqliteResult<Option<Todo>> {
+        let mut todos = self.todos.lock().unwrap();
+        let Some(todo) = todos
+            .iter_mut()
+            .find(|t| t.id == id && t.deleted_at.is_none())
+        else {
+            return Ok(None);
+        };
+        todo.recurrence = recurrence;
+        todo.next_run_at = next_run_at;
+        Ok(Some(todo.clone()))
+    }
+
+    fn due_recurring(&self, now: &str) -> SqliteResult<Vec<Todo>> {
+        let mut todos: Vec<Todo> = self
+            .todos
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|t| t.recurrence.is_some() && t.deleted_at.is_none())
+            .filter(|t| t.status != TodoStatus::Archived)
+            .filter(|t| t.next_run_at.as_deref().is_some_and(|at| at <= now))
+            .cloned()
+            .collect();
+        todos.sort_by(|a, b| a.next_run_at.cmp(&b.next_run_at));
+        Ok(todos)
+    }
+}
+
+fn new_todo(input: CreateTodoInput) -> Todo {
+    let now = Utc::now().to_rfc3339();
+    Todo {
+        id: Uuid::new_v4().to_string(),
+        title: input.title,
+        description: input.description,
+        url: input.url,
+        status: input.status.unwrap_or(TodoStatus::Pending),
+        created_at: now.clone(),
+        updated_at: now,
+        tags: input.tags,
+        due_date: input.due_date,
+        deleted_at: None,
+        recurrence: None,
+        next_run_at: None,
+    }
+}
+
+/// Fields left as `None` in the input keep their current value.
+fn apply_update(current: Todo, input: UpdateTodoInput) -> Todo {
+    Todo {
+        title: input.title.unwrap_or(current.title),
+        description: input.description.or(current.description),
+        url: input.url.or(current.url),
+        status: input.status.unwrap_or(current.status),
+        tags: input.tags.unwrap_or(current.tags),
+        due_date: input.due_date.or(current.due_date),
+        updated_at: Utc::now().to_rfc3339(),
+        ..current
+    }
+}
+
+fn new_research(
+    todo_id: &str,
+    source: &str,
+    content: &str,
+    raw_html: Option<&str>,
+    started_at: &str,
+) -> ResearchResult {
+    let completed = Utc::now();
+    let started: DateTime<Utc> = started_at.parse().unwrap_or(completed);
+
+    ResearchResult {
+        id: Uuid::new_v4().to_string(),
+        todo_id: todo_id.to_string(),
+        source: source.to_string(),
+        content: Some(content.to_string()),
+        raw_html: raw_html.map(|s| s.to_string()),
+        started_at: Some(started_at.to_string()),
+        completed_at: Some(completed.to_rfc3339()),
+        duration_seconds: Some((completed - started).num_seconds()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sqlite_conn() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("PRAGMA foreign_keys = ON;").unwrap();
+        conn.execute_batch(include_str!("../migrations/001_init.sql"))
+            .unwrap();
+        conn
+    }
+
+    fn input(title: &str) -> CreateTodoInput {
+        CreateTodoInput {
+            title: title.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Runs the same assertions against every implementation.
+    fn each_repository(check: fn(&dyn TodoRepository)) {
+        let conn = sqlite_conn();
+        check(&SqliteTodoRepository::new(&conn));
+        check(&MemoryTodoRepository::new());
+    }
+
+    #[test]
+    fn test_create_and_get() {
+        each_repository(|repo| {
+            let created = repo
+                .create(CreateTodoInput {
+                    title: "Market scan".to_string(),
+                    description: Some("EV batteries".to_string()),
+                    url: Some("https://example.com".to_string()),
+                    status: None,
+                    tags: vec!["ev".to_string(), "q3".to_string()],
+                    due_date: Some("2024-09-30".to_string()),
+                })
+                .unwrap();
+            assert_eq!(created.status, TodoStatus::Pending);
+
+            let fetched = repo.get(&created.id).unwrap().unwrap();
+            assert_eq!(fetched.title, "Market scan");
+            assert_eq!(fetched.description.as_deref(), Some("EV batteries"));
+            assert_eq!(fetched.tags, vec!["ev".to_string(), "q3".to_string()]);
+            assert_eq!(fetched.due_date.as_deref(), Some("2024-09-30"));
+            assert_eq!(fetched.created_at, created.created_at);
+
+            assert!(repo.get("missing").unwrap().is_none());
+        });
+    }
+
+    #[test]
+    fn test_list_filters_and_orders() {
+        each_repository(|repo| {
+            let first = repo.create(input("First")).unwrap();
+            let second = repo.create(input("Second")).unwrap();
+            repo.create(CreateTodoInput {
+                status: Some(TodoStatus::Archived),
+                ..input("Old")
+            })
+            .unwrap();
+
+            let all = repo.list(None).unwrap();
+            let ids: Vec<&str> = all.iter().map(|t| t.id.as_str()).collect();
+            assert_eq!(ids, vec![second.id.as_str(), first.id.as_str()]);
+
+            let archived = repo.list(Some("archived")).unwrap();
+            assert_eq!(archived.len(), 1);
+            assert_eq!(archived[0].title, "Old");
+
+            assert!(repo.list(Some("done")).unwrap().is_empty());
+        });
+    }
+
+    #[test]
+    fn test_update_merges_fields() {
+        each_repository(|repo| {
+            let created = repo
+                .create(CreateTodoInput {
+                    description: Some("keep me".to_string()),
+                    tags: vec!["a".to_string()],
+                    ..input("Draft")
+                })
+                .unwrap();
+
+            let updated = repo
+                .update(
+                    &created.id,
+                    UpdateTodoInput {
+                        title: Some("Final".to_string()),
+                        status: Some(TodoStatus::Done),
+                        ..Default::default()
+                    },
+                )
+                .unwrap()
+                .unwrap();
+            assert_eq!(updated.title, "Final");
+            assert_eq!(updated.status, TodoStatus::Done);
+            assert_eq!(updated.description.as_deref(), Some("keep me"));
+            assert_eq!(updated.tags, vec!["a".to_string()]);
+            assert_eq!(updated.created_at, created.created_at);
+
+            let stored = repo.get(&created.id).unwrap().unwrap();
+            assert_eq!(stored.title, "Final");
+            assert_eq!(stored.status, TodoStatus::Done);
+
+            let missing = repo.update("missing", UpdateTodoInput::default()).unwrap();
+            assert!(missing.is_none());
+        });
+    }
+
+    #[test]
+    fn test_delete_moves_to_trash_and_restores() {
+        each_repository(|repo| {
+            let todo = repo.create(input("Delete me")).unwrap();
+            repo.save_research(&todo.id, "chatgpt", "# Report", None, None, "")
+                .unwrap();
+
+            assert!(repo.delete(&todo.id).unwrap());
+            assert!(!repo.delete(&todo.id).unwrap());
+            assert!(repo.get(&todo.id).unwrap().is_none());
+            assert!(repo.list(None).unwrap().is_empty());
+            assert_eq!(repo.status_counts().unwrap().total, 0);
+
+            let trash = repo.list_trash().unwrap();
+            assert_eq!(trash.len(), 1);
+            assert!(trash[0].deleted_at.is_some());
+
+            let restored = repo.restore(&todo.id).unwrap().unwrap();
+            assert!(restored.deleted_at.is_none());
+            assert!(repo.restore(&todo.id).unwrap().is_none());
+            assert!(repo.list_trash().unwrap().is_empty());
+            assert!(repo.latest_research(&todo.id).unwrap().is_some());
+        });
+    }
+
+    #[test]
+    fn test_purge_removes_research() {
+        each_repository(|repo| {
+            let todo = repo.create(input("Purge me")).unwrap();
+            repo.save_research(&todo.id, "chatgpt", "# Report", None, None, "")
+                .unwrap();
+
+            // Only trashed todos can be purged.
+            assert!(!repo.purge(&todo.id).unwrap());
+
+            repo.delete(&todo.id).unwrap();
+            assert!(repo.purge(&todo.id).unwrap());
+            assert!(repo.list_trash().unwrap().is_empty());
+            assert!(repo.latest_research(&todo.id).unwrap().is_none());
+        });
+    }
+
+    #[test]
+    fn test_purge_deleted_before_cutoff() {
+        each_repository(|repo| {
+            let kept = repo.create(input("Kept")).unwrap();
+            let trashed = repo.create(input("Trashed")).unwrap();
+            repo.delete(&trashed.id).unwrap();
+
+            let past = (Utc::now() - chrono::Duration::days(1)).to_rfc3339();
+            assert_eq!(repo.purge_deleted_before(&past).unwrap(), 0);
+
+            let future = (Utc::now() + chrono::Duration::days(1)).to_rfc3339();
+            assert_eq!(repo.purge_deleted_before(&future).unwrap(), 1);
+            assert!(repo.list_trash().unwrap().is_empty());
+            assert!(repo.get(&kept.id).unwrap().is_some());
+        });
+    }
+
+    #[test]
+    fn test_status_counts() {
+        each_repository(|repo| {
+            repo.create(input("One")).unwrap();
+            repo.create(input("Two")).unwrap();
+            for status in [TodoStatus::Review, TodoStatus::Done, TodoStatus::Archived] {
+                repo.create(CreateTodoInput {
+                    status: Some(status),
+                    ..input("Other")
+                })
+                .unwrap();
+            }
+
+            let counts = repo.status_counts().unwrap();
+            assert_eq!(counts.pending, 2);
+            assert_eq!(counts.researching, 0);
+            assert_eq!(counts.review, 1);
+            assert_eq!(counts.done, 1);
+            assert_eq!(counts.archived, 1);
+            assert_eq!(counts.total, 5);
+        });
+    }
+
+    #[test]
+    fn test_save_research_moves_to_review() {
+        each_repository(|repo| {
+            let todo = repo
+                .create(CreateTodoInput {
+                    status: Some(TodoStatus::Researching),
+                    ..input("Research me")
+                })
+                .unwrap();
+
+            let started_at = (Utc::now() - chrono::Duration::seconds(90)).to_rfc3339();
+            let saved = repo
+                .save_research(
+                    &todo.id,
+                    "chatgpt",
+                    "# Findings",
+                    Some("<h1>Findings</h1>"),
+                    Some(1),
+                    &started_at,
+                )
+                .unwrap();
+            assert_eq!(saved.content.as_deref(), Some("# Findings"));
+            assert!(saved.duration_seconds.unwrap() >= 90);
+
+            let latest = repo.latest_research(&todo.id).unwrap().unwrap();
+            assert_eq!(latest.id, saved.id);
+            assert_eq!(latest.raw_html.as_deref(), Some("<h1>Findings</h1>"));
+
+            let stored = repo.get(&todo.id).unwrap().unwrap();
+            assert_eq!(stored.status, TodoStatus::Review);
+        });
+    }
+
+    #[test]
+    fn test_research_history_keeps_reruns() {
+        each_repository(|repo| {
+            let todo = repo.create(input("Monthly market scan")).unwrap();
+            let first = repo
+                .save_research(&todo.id, "chatgpt", "# January", None, None, "")
+                .unwrap();
+            let second = repo
+                .save_research(&todo.id, "chatgpt", "# February", None, None, "")
+                .unwrap();
+
+            let history = repo.list_research(&todo.id).unwrap();
+            let ids: Vec<&str> = history.iter().map(|r| r.id.as_str()).collect();
+            assert_eq!(ids, vec![second.id.as_str(), first.id.as_str()]);
+        });
+    }
+
+    #[test]
+    fn test_due_recurring() {
+        each_repository(|repo| {
+            let due = repo.create(input("Due")).unwrap();
+            let later = repo.create(input("Later")).unwrap();
+            let archived = repo
+                .create(CreateTodoInput {
+                    status: Some(TodoStatus::Archived),
+                    ..input("Archived")
+                })
+                .unwrap();
+            repo.create(input("One-off")).unwrap();
+
+            let past = "2024-01-01T00:00:00+00:00".to_string();
+            let future = "2099-01-01T00:00:00+00:00".to_string();
+            let monthly = Recurrence::Monthly { anchor: None };
+            for (id, next) in [
+                (&due.id, &past),
+                (&later.id, &future),
+                (&archived.id, &past),
+            ] {
+                let updated = repo
+                    .set_recurrence(id, Some(monthly.clone()), Some(next.clone()))
+                    .unwrap()
+                    .unwrap();
+                assert_eq!(updated.recurrence, Some(monthly.clone()));
+            }
+
+            let now = Utc::now().to_rfc3339();
+            let found = repo.due_recurring(&now).unwrap();
+            assert_eq!(found.len(), 1);
+            assert_eq!(found[0].id, due.id);
+
+            let cleared = repo.set_recurrence(&due.id, None, None).unwrap().unwrap();
+            assert!(cleared.recurrence.is_none());
+            assert!(repo.due_recurring(&now).unwrap().is_empty());
+        });
+    }
+
+    #[test]
+    fn test_save_research_for_missing_todo_fails() {
+        each_repository(|repo| {
+            let err = repo
+                .save_research("missing", "chatgpt", "# Report", None, None, "")
+                .unwrap_err();
+            assert_eq!(
+                err.sqlite_error_code(),
+                Some(rusqlite::ErrorCode::ConstraintViolation)
+            );
+        });
+    }
+}
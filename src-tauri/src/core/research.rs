@@ -1,4 +1,30 @@
+use log::{error, info, warn};
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    sync::{Mutex, MutexGuard},
+    time::Duration,
+};
+use tauri::{AppHandle, Emitter, Listener, Manager};
+
+use crate::core::{
+    error::AppError,
+    markdown,
+    notify::{self, Notice},
+    todo::{self, Todo, TodoStatus, UpdateTodoInput},
+};
+
+/// Emitted by the DeepResearch runner when a report is ready.
+pub static RESEARCH_COMPLETE: &str = "research_complete";
+/// Emitted by the DeepResearch runner when a run could not finish.
+pub static RESEARCH_FAILED: &str = "research_failed";
+/// Broadcast to the app windows once a run has been saved or failed.
+pub static RESEARCH_UPDATED: &str = "research_updated";
+
+/// Runs that have not reported back by then are failed, so a reloaded page or a lost
+/// event cannot hold the queue forever.
+const RUN_TIMEOUT: Duration = Duration::from_secs(60 * 60);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResearchResult {
@@ -11,3 +37,349 @@ pub struct ResearchResult {
     pub completed_at: Option<String>,
     pub duration_seconds: Option<i64>,
 }
+
+/// Todos waiting for the single DeepResearch runner in the main webview.
+#[derive(Default)]
+struct ResearchQueue {
+    active: Option<String>,
+    pending: VecDeque<String>,
+    /// Runs finished since the queue was last idle.
+    finished: usize,
+    /// Bumped for every dispatched run, so a timeout only fails the run it was set for.
+    run: u64,
+}
+
+impl ResearchQueue {
+    /// Records `id` as the running todo and arms its timeout.
+    fn activate(&mut self, app: &AppHandle, id: String) {
+        self.run += 1;
+        watch(app, id.clone(), self.run);
+        self.active = Some(id);
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CompletePayload {
+    todo_id: String,
+    source: String,
+    content: String,
+    raw_html: Option<String>,
+    started_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FailedPayload {
+    todo_id: String,
+    error: String,
+}
+
+static QUEUE: Lazy<Mutex<ResearchQueue>> = Lazy::new(Default::default);
+
+fn queue() -> MutexGuard<'static, ResearchQueue> {
+    QUEUE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Whether a run is active or waiting.
+pub fn is_busy() -> bool {
+    let queue = queue();
+    queue.active.is_some() || !queue.pending.is_empty()
+}
+
+/// Research can start from any status except while already running or once archived.
+pub fn check_can_start(todo: &Todo) -> Result<(), AppError> {
+    if matches!(todo.status, TodoStatus::Researching | TodoStatus::Archived) {
+        return Err(AppError::InvalidTransition(format!(
+            "Cannot start research on a todo that is {}",
+            todo.status.as_str()
+        )));
+    }
+    Ok(())
+}
+
+/// Sends a todo to `window.DeepResearch.start` in the main webview.
+pub fn dispatch(app: &AppHandle, todo: &Todo) -> Result<(), AppError> {
+    let webview = app
+        .get_window("core")
+        .and_then(|win| win.get_webview("main"))
+        .ok_or_else(|| AppError::Webview("Main webview is not available".to_string()))?;
+
+    let prompt = format!(
+        "Please research: {}. Context: {}",
+        todo.title,
+        todo.description
+            .as_deref()
+            .unwrap_or("No description provided")
+    );
+    let script = format!(
+        "window.DeepResearch.start({}, {})",
+        serde_json::to_string(&todo.id)?,
+        serde_json::to_string(&prompt)?
+    );
+    webview.eval(&script)?;
+    Ok(())
+}
+
+/// Starts research on `todo` right away, or queues it behind the running one. Returns
+/// whether it was dispatched immediately.
+pub fn start(app: &AppHandle, todo: &Todo) -> Result<bool, AppError> {
+    let mut queue = queue();
+    if queue.active.is_some() {
+        queue.pending.push_back(todo.id.clone());
+        info!("[research] Queued {}", todo.id);
+        return Ok(false);
+    }
+
+    dispatch(app, todo)?;
+    queue.activate(app, todo.id.clone());
+    Ok(true)
+}
+
+/// Marks a todo as researching and hands it to the runner, restoring its previous status
+/// if the webview cannot take it. Returns `None` when the todo does not exist.
+pub fn begin(app: &AppHandle, id: &str) -> Result<Option<Todo>, AppError> {
+    let Some(t) = todo::get_todo_by_id(id)? else {
+        return Ok(None);
+    };
+    check_can_start(&t)?;
+
+    let input = UpdateTodoInput {
+        status: Some(TodoStatus::Researching),
+        ..Default::default()
+    };
+    let updated = todo::update_todo(id, input)?;
+
+    // Trigger research in the main webview, or queue it behind the running one
+    if let Err(e) = start(app, &t) {
+        let rollback = UpdateTodoInput {
+            status: Some(t.status),
+            ..Default::default()
+        };
+        todo::update_todo(id, rollback)?;
+        return Err(e);
+    }
+
+    Ok(updated)
+}
+
+/// Queues several todos that are already marked as researching and starts the first one
+/// if the runner is idle.
+pub fn enqueue(app: &AppHandle, ids: Vec<String>) {
+    let idle = {
+        let mut queue = queue();
+        queue.pending.extend(ids);
+        queue.active.is_none()
+    };
+    if idle {
+        run_next(app);
+    }
+}
+
+/// Marks the research of `id` as finished or cancelled and starts the next queued todo.
+pub fn finish(app: &AppHandle, id: &str) {
+    let was_active = {
+        let mut queue = queue();
+        queue.pending.retain(|queued| queued != id);
+        if queue.active.as_deref() == Some(id) {
+            queue.active = None;
+            queue.finished += 1;
+            true
+        } else {
+            false
+        }
+    };
+    if was_active {
+        run_next(app);
+    }
+}
+
+/// Stops the research of `id`: the webview runner is only cancelled when `id` is the one
+/// running, queued todos are just dropped from the queue.
+pub fn cancel(app: &AppHandle, id: &str) {
+    let is_active = queue().active.as_deref() == Some(id);
+    if is_active {
+        cancel_runner(app);
+    }
+    finish(app, id);
+}
+
+fn cancel_runner(app: &AppHandle) {
+    if let Some(webview) = app
+        .get_window("core")
+        .and_then(|win| win.get_webview("main"))
+    {
+        let _ = webview.eval("window.DeepResearch.cancel()");
+    }
+}
+
+/// Marks a run as failed: the todo goes back to pending and the next queued todo starts.
+pub fn fail(app: &AppHandle, id: &str, reason: &str) -> Result<Option<Todo>, AppError> {
+    error!("[research] {} failed: {}", id, reason);
+    let Some(t) = todo::get_todo_by_id(id)? else {
+        finish(app, id);
+        return Ok(None);
+    };
+    notify::send(app, Notice::research_failed(id, &t.title, reason));
+
+    let todo = if t.status == TodoStatus::Researching {
+        let rollback = UpdateTodoInput {
+            status: Some(TodoStatus::Pending),
+            ..Default::default()
+        };
+        todo::update_todo(id, rollback)?
+    } else {
+        Some(t)
+    };
+
+    finish(app, id);
+    Ok(todo)
+}
+
+/// Stores a finished run and starts the next queued todo.
+fn complete(app: &AppHandle, payload: &CompletePayload) {
+    match save(payload) {
+        Ok(t) => notify::send(app, Notice::research_complete(&t.id, &t.title)),
+        Err(e) => error!("[research] Failed to save {}: {}", payload.todo_id, e),
+    }
+    // The queue moves on even when saving failed, so it never stalls on one result.
+    finish(app, &payload.todo_id);
+}
+
+/// Saves a result, rendering its content from the raw HTML.
+fn save(payload: &CompletePayload) -> Result<Todo, AppError> {
+    let t = todo::get_todo_by_id(&payload.todo_id)?
+        .ok_or_else(|| AppError::NotFound(format!("Todo not found: {}", payload.todo_id)))?;
+
+    // The page's own markdown is only a fallback for when the HTML is missing.
+    let raw_html = payload.raw_html.as_deref();
+    let converted = raw_html
+        .map(markdown::convert)
+        .filter(|converted| !converted.markdown.is_empty());
+    let content = converted
+        .as_ref()
+        .map_or(payload.content.as_str(), |converted| {
+            converted.markdown.as_str()
+        });
+
+    todo::save_research_result(
+        &payload.todo_id,
+        &payload.source,
+        content,
+        raw_html,
+        raw_html.is_some().then_some(markdown::CONVERTER_VERSION),
+        &payload.started_at,
+    )?;
+    Ok(t)
+}
+
+/// Handles the runner's results in the backend, so runs are saved and the queue moves on
+/// whether or not an app window is open.
+pub fn listen(app: &AppHandle) {
+    let handle = app.clone();
+    app.listen(RESEARCH_COMPLETE, move |event| {
+        let payload: CompletePayload = match serde_json::from_str(event.payload()) {
+            Ok(payload) => payload,
+            Err(e) => {
+                error!("[research] Malformed result: {}", e);
+                return;
+            }
+        };
+        let app = handle.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            complete(&app, &payload);
+            broadcast(&app, &payload.todo_id);
+        });
+    });
+
+    let handle = app.clone();
+    app.listen(RESEARCH_FAILED, move |event| {
+        let payload: FailedPayload = match serde_json::from_str(event.payload()) {
+            Ok(payload) => payload,
+            Err(e) => {
+                error!("[research] Malformed failure: {}", e);
+                return;
+            }
+        };
+        let app = handle.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            if let Err(e) = fail(&app, &payload.todo_id, &payload.error) {
+                error!("[research] Failed to reset {}: {}", payload.todo_id, e);
+            }
+            broadcast(&app, &payload.todo_id);
+        });
+    });
+}
+
+fn broadcast(app: &AppHandle, id: &str) {
+    if let Err(e) = app.emit(RESEARCH_UPDATED, id) {
+        error!("[research] Failed to broadcast update: {}", e);
+    }
+}
+
+/// Fails run `run` of `id` if it is still the active one after [`RUN_TIMEOUT`].
+fn watch(app: &AppHandle, id: String, run: u64) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(RUN_TIMEOUT).await;
+        let timed_out = {
+            let queue = queue();
+            queue.run == run && queue.active.as_deref() == Some(id.as_str())
+        };
+        if !timed_out {
+            return;
+        }
+
+        warn!("[research] {} timed out", id);
+        cancel_runner(&app);
+        tauri::async_runtime::spawn_blocking(move || {
+            if let Err(e) = fail(&app, &id, "Research timed out") {
+                error!("[research] Failed to reset {}: {}", id, e);
+            }
+            broadcast(&app, &id);
+        });
+    });
+}
+
+fn run_next(app: &AppHandle) {
+    loop {
+        let next = queue().pending.pop_front();
+        let Some(id) = next else {
+            let finished = std::mem::take(&mut queue().finished);
+            notify::research_idle(app, finished);
+            return;
+        };
+
+        // Skip todos that were cancelled, edited or deleted while waiting.
+        let todo = match todo::get_todo_by_id(&id) {
+            Ok(Some(todo)) if todo.status == TodoStatus::Researching => todo,
+            Ok(_) => continue,
+            Err(e) => {
+                error!("[research] Failed to load queued todo {}: {}", id, e);
+                continue;
+            }
+        };
+
+        match dispatch(app, &todo) {
+            Ok(()) => {
+                queue().activate(app, id);
+                info!("[research] Started queued {}", todo.id);
+                return;
+            }
+            Err(e) => {
+                error!("[research] Failed to start {}: {}", id, e);
+                notify::send(
+                    app,
+                    Notice::research_failed(&id, &todo.title, &e.to_string()),
+                );
+                let rollback = UpdateTodoInput {
+                    status: Some(TodoStatus::Pending),
+                    ..Default::default()
+                };
+                if let Err(e) = todo::update_todo(&id, rollback) {
+                    error!("[research] Failed to reset {}: {}", id, e);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,249 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use log::{error, info};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::core::{db, error::AppError, todo::TodoStatus};
+
+/// Settings key holding the rules as a JSON array.
+pub static RULES_KEY: &str = "status_rules";
+
+/// How often the rules are evaluated after the startup run.
+const RULES_INTERVAL: std::time::Duration = std::time::Duration::from_secs(3600);
+
+/// Moves todos from one status to another once they have not been touched for
+/// `after_days` days.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StatusRule {
+    pub name: String,
+    pub enabled: bool,
+    pub from_status: TodoStatus,
+    pub to_status: TodoStatus,
+    pub after_days: u32,
+}
+
+impl StatusRule {
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.name.trim().is_empty() {
+            return Err(AppError::Validation("Rule name is required".to_string()));
+        }
+        if self.after_days == 0 {
+            return Err(AppError::Validation(format!(
+                "Rule '{}' must wait at least one day",
+                self.name
+            )));
+        }
+        if self.from_status == self.to_status {
+            return Err(AppError::Validation(format!(
+                "Rule '{}' does not change the status",
+                self.name
+            )));
+        }
+        // Research runs are owned by the research queue.
+        if [&self.from_status, &self.to_status].contains(&&TodoStatus::Researching) {
+            return Err(AppError::Validation(format!(
+                "Rule '{}' cannot move todos into or out of researching",
+                self.name
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// A status change made by a rule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleChange {
+    pub rule: String,
+    pub todo_id: String,
+    pub title: String,
+    pub from_status: TodoStatus,
+    pub to_status: TodoStatus,
+}
+
+/// Rules used until the user saves their own.
+pub fn default_rules() -> Vec<StatusRule> {
+    vec![
+        StatusRule {
+            name: "Archive done todos".to_string(),
+            enabled: true,
+            from_status: TodoStatus::Done,
+            to_status: TodoStatus::Archived,
+            after_days: 14,
+        },
+        StatusRule {
+            name: "Reopen stale reviews".to_string(),
+            enabled: false,
+            from_status: TodoStatus::Review,
+            to_status: TodoStatus::Pending,
+            after_days: 30,
+        },
+    ]
+}
+
+pub fn load_rules() -> Result<Vec<StatusRule>> {
+    match db::get_setting(RULES_KEY)? {
+        Some(json) => serde_json::from_str(&json).context("Stored status rules are malformed"),
+        None => Ok(default_rules()),
+    }
+}
+
+pub fn save_rules(rules: &[StatusRule]) -> Result<(), AppError> {
+    for rule in rules {
+        rule.validate()?;
+    }
+    db::set_setting(RULES_KEY, &serde_json::to_string(rules)?)?;
+    Ok(())
+}
+
+/// Applies every enabled rule in one transaction. Rules run in order, and a moved todo
+/// counts as touched, so one run never chains two rules on the same todo.
+pub fn evaluate(
+    conn: &mut Connection,
+    rules: &[StatusRule],
+    now: DateTime<Utc>,
+) -> Result<Vec<RuleChange>> {
+    let tx = conn.transaction()?;
+    let now_str = now.to_rfc3339();
+    let mut changes = Vec::new();
+
+    for rule in rules.iter().filter(|r| r.enabled) {
+        if let Err(e) = rule.validate() {
+            error!("[rules] Skipping invalid rule: {}", e);
+            continue;
+        }
+
+        let cutoff = (now - Duration::days(rule.after_days as i64)).to_rfc3339();
+        let matched: Vec<(String, String)> = {
+            let mut stmt = tx.prepare(
+                "SELECT id, title FROM todos
+                 WHERE status = ?1 AND deleted_at IS NULL AND julianday(updated_at) < julianday(?2)",
+            )?;
+            let rows = stmt.query_map(params![rule.from_status.as_str(), cutoff], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?;
+            rows.collect::<rusqlite::Result<_>>()?
+        };
+
+        for (todo_id, title) in matched {
+            tx.execute(
+                "UPDATE todos SET status = ?1, updated_at = ?2 WHERE id = ?3",
+                params![rule.to_status.as_str(), now_str, todo_id],
+            )?;
+            changes.push(RuleChange {
+                rule: rule.name.clone(),
+                todo_id,
+                title,
+                from_status: rule.from_status.clone(),
+                to_status: rule.to_status.clone(),
+            });
+        }
+    }
+
+    tx.commit()?;
+    Ok(changes)
+}
+
+/// Evaluates the stored rules against the app database and logs every change.
+pub fn run_now() -> Result<Vec<RuleChange>> {
+    let rules = load_rules()?;
+    let changes = evaluate(&mut db::writer(), &rules, Utc::now())?;
+
+    for change in &changes {
+        info!(
+            "[rules] {}: '{}' ({}) {} -> {}",
+            change.rule,
+            change.title,
+            change.todo_id,
+            change.from_status.as_str(),
+            change.to_status.as_str()
+        );
+    }
+    Ok(changes)
+}
+
+/// Runs the rules on startup and then every hour.
+pub fn start() {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Err(e) = run_now() {
+                error!("[rules] {}", e);
+            }
+            tokio::time::sleep(RULES_INTERVAL).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(include_str!("../migrations/001_init.sql"))
+            .unwrap();
+        conn
+    }
+
+    fn seed(conn: &Connection, id: &str, status: &str, updated_at: &str) {
+        conn.execute(
+            "INSERT INTO todos (id, title, status, created_at, updated_at) VALUES (?1, ?1, ?2, ?3, ?3)",
+            params![id, status, updated_at],
+        )
+        .unwrap();
+    }
+
+    fn status_of(conn: &Connection, id: &str) -> String {
+        conn.query_row("SELECT status FROM todos WHERE id = ?1", [id], |row| {
+            row.get(0)
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_rules_move_stale_todos() {
+        let mut conn = open_db();
+        let now: DateTime<Utc> = "2024-06-30T12:00:00Z".parse().unwrap();
+        seed(&conn, "old-done", "done", "2024-06-01T00:00:00Z");
+        seed(&conn, "new-done", "done", "2024-06-25T00:00:00+00:00");
+        seed(&conn, "old-review", "review", "2024-05-01T00:00:00Z");
+        seed(&conn, "old-pending", "pending", "2024-01-01T00:00:00Z");
+
+        let mut rules = default_rules();
+        rules[1].enabled = true;
+
+        let changes = evaluate(&mut conn, &rules, now).unwrap();
+        let moved: Vec<&str> = changes.iter().map(|c| c.todo_id.as_str()).collect();
+        assert_eq!(moved, vec!["old-done", "old-review"]);
+
+        assert_eq!(status_of(&conn, "old-done"), "archived");
+        assert_eq!(status_of(&conn, "new-done"), "done");
+        assert_eq!(status_of(&conn, "old-review"), "pending");
+        assert_eq!(status_of(&conn, "old-pending"), "pending");
+
+        // Moved todos count as touched.
+        assert!(evaluate(&mut conn, &rules, now).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_disabled_and_invalid_rules_are_skipped() {
+        let mut conn = open_db();
+        seed(&conn, "stuck", "researching", "2024-01-01T00:00:00Z");
+        seed(&conn, "review", "review", "2024-01-01T00:00:00Z");
+
+        let invalid = StatusRule {
+            name: "Unstick research".to_string(),
+            enabled: true,
+            from_status: TodoStatus::Researching,
+            to_status: TodoStatus::Pending,
+            after_days: 1,
+        };
+        assert_eq!(invalid.validate().unwrap_err().code(), "Validation");
+
+        let mut rules = default_rules();
+        rules.push(invalid);
+        assert!(evaluate(&mut conn, &rules, Utc::now()).unwrap().is_empty());
+        assert_eq!(status_of(&conn, "stuck"), "researching");
+        assert_eq!(status_of(&conn, "review"), "review");
+    }
+}
@@ -0,0 +1,411 @@
+use log::warn;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fs, path::PathBuf};
+
+use crate::core::{
+    error::AppError,
+    template::{self, Template, SCRIPTS},
+};
+
+/// Subdirectory of the scripts directory holding scripts the user added.
+pub static CUSTOM_DIR: &str = "custom";
+
+/// Pattern used for scripts without `@match` headers or user patterns.
+pub static MATCH_ALL: &str = "*";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScriptOrigin {
+    /// Shipped with the app and kept up to date by [`Template`].
+    Bundled,
+    /// A bundled script replaced by a file in the overrides directory.
+    Overridden,
+    /// Added by the user.
+    Custom,
+}
+
+/// An installed script with its header metadata and the user's settings.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UserScript {
+    /// File name, which identifies the script.
+    pub file: String,
+    pub name: String,
+    pub version: String,
+    pub url: String,
+    pub origin: ScriptOrigin,
+    pub enabled: bool,
+    /// Patterns from the script's `@match` headers.
+    pub default_matches: Vec<String>,
+    /// Patterns set by the user, replacing the defaults.
+    pub matches: Option<Vec<String>>,
+}
+
+impl UserScript {
+    /// The URL patterns the script runs on.
+    pub fn effective_matches(&self) -> Vec<String> {
+        match &self.matches {
+            Some(matches) => matches.clone(),
+            None if self.default_matches.is_empty() => vec![MATCH_ALL.to_string()],
+            None => self.default_matches.clone(),
+        }
+    }
+}
+
+/// Turns a userscript-style pattern into an anchored regex: `*` matches anything, every
+/// other character matches itself. The result is valid in both Rust and JavaScript.
+fn pattern_regex(pattern: &str) -> String {
+    let parts: Vec<String> = pattern.split('*').map(regex::escape).collect();
+    format!("^{}$", parts.join(".*"))
+}
+
+pub fn validate_pattern(pattern: &str) -> Result<(), AppError> {
+    if pattern.is_empty() || pattern.chars().any(char::is_whitespace) {
+        return Err(AppError::Validation(format!(
+            "Invalid match pattern: '{}'",
+            pattern
+        )));
+    }
+    Ok(())
+}
+
+fn is_bundled(file: &str) -> bool {
+    SCRIPTS.iter().any(|(name, _)| *name == file)
+}
+
+fn custom_path(template: &Template, file: &str) -> PathBuf {
+    template.dir.join(CUSTOM_DIR).join(file)
+}
+
+fn validate_file_name(file: &str) -> Result<(), AppError> {
+    let valid = file.len() > 3
+        && file.ends_with(".js")
+        && !file.starts_with('.')
+        && file
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        return Err(AppError::Validation(format!(
+            "Script names must end in .js and use only letters, digits, '-', '_' and '.': '{}'",
+            file
+        )));
+    }
+    Ok(())
+}
+
+/// The source of a script and where it comes from.
+fn read_source(template: &Template, file: &str) -> Result<(ScriptOrigin, String), AppError> {
+    if is_bundled(file) {
+        let origin = if template.override_path(file).exists() {
+            ScriptOrigin::Overridden
+        } else {
+            ScriptOrigin::Bundled
+        };
+        return Ok((origin, template.load(file)));
+    }
+    validate_file_name(file)?;
+    let source = fs::read_to_string(custom_path(template, file))
+        .map_err(|_| AppError::NotFound(format!("Script not found: {}", file)))?;
+    Ok((ScriptOrigin::Custom, source))
+}
+
+/// File names of the custom scripts, sorted.
+fn custom_files(template: &Template) -> Vec<String> {
+    let mut files: Vec<String> = fs::read_dir(template.dir.join(CUSTOM_DIR))
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_file())
+                .filter_map(|entry| entry.file_name().into_string().ok())
+                .filter(|file| validate_file_name(file).is_ok() && !is_bundled(file))
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+/// The user's settings of one script, as stored.
+#[derive(Debug, Clone)]
+struct ScriptSettings {
+    enabled: bool,
+    matches: Option<Vec<String>>,
+}
+
+/// Stored settings by file name.
+fn stored(conn: &Connection) -> Result<HashMap<String, ScriptSettings>, AppError> {
+    let mut stmt = conn.prepare("SELECT file, enabled, matches FROM user_scripts")?;
+    let rows = stmt.query_map([], |row| {
+        let matches: Option<String> = row.get(2)?;
+        Ok((
+            row.get::<_, String>(0)?,
+            ScriptSettings {
+                enabled: row.get(1)?,
+                matches: matches.and_then(|json| serde_json::from_str(&json).ok()),
+            },
+        ))
+    })?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+fn describe(
+    template: &Template,
+    file: &str,
+    settings: Option<&ScriptSettings>,
+) -> Result<UserScript, AppError> {
+    let (origin, source) = read_source(template, file)?;
+    let info = template::read_version_info(source.as_bytes())?;
+    let field = |key: &str| {
+        info.get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string()
+    };
+    let name = field("name");
+    let settings = settings.cloned().unwrap_or(ScriptSettings {
+        enabled: true,
+        matches: None,
+    });
+
+    Ok(UserScript {
+        file: file.to_string(),
+        name: if name.is_empty() {
+            file.to_string()
+        } else {
+            name
+        },
+        version: field("version"),
+        url: field("url"),
+        origin,
+        enabled: settings.enabled,
+        default_matches: template::read_matches(source.as_bytes()),
+        matches: settings.matches,
+    })
+}
+
+/// Every installed script: the bundled ones first, then custom scripts by file name.
+pub fn list(conn: &Connection, template: &Template) -> Result<Vec<UserScript>, AppError> {
+    let settings = stored(conn)?;
+    SCRIPTS
+        .iter()
+        .map(|(file, _)| file.to_string())
+        .chain(custom_files(template))
+        .map(|file| describe(template, &file, settings.get(&file)))
+        .collect()
+}
+
+pub fn get(conn: &Connection, template: &Template, file: &str) -> Result<UserScript, AppError> {
+    describe(template, file, stored(conn)?.get(file))
+}
+
+pub fn set_enabled(
+    conn: &Connection,
+    template: &Template,
+    file: &str,
+    enabled: bool,
+) -> Result<UserScript, AppError> {
+    read_source(template, file)?;
+    conn.execute(
+        "INSERT INTO user_scripts (file, enabled) VALUES (?1, ?2)
+         ON CONFLICT(file) DO UPDATE SET enabled = excluded.enabled",
+        params![file, enabled],
+    )?;
+    get(conn, template, file)
+}
+
+/// Sets the URL patterns a script runs on; `None` goes back to its `@match` headers.
+pub fn set_matches(
+    conn: &Connection,
+    template: &Template,
+    file: &str,
+    matches: Option<Vec<String>>,
+) -> Result<UserScript, AppError> {
+    read_source(template, file)?;
+    let matches = match matches {
+        Some(matches) => {
+            if matches.is_empty() {
+                return Err(AppError::Validation(
+                    "A script needs at least one match pattern; disable it instead".to_string(),
+                ));
+            }
+            for pattern in &matches {
+                validate_pattern(pattern)?;
+            }
+            Some(serde_json::to_string(&matches)?)
+        }
+        None => None,
+    };
+    conn.execute(
+        "INSERT INTO user_scripts (file, matches) VALUES (?1, ?2)
+         ON CONFLICT(file) DO UPDATE SET matches = excluded.matches",
+        params![file, matches],
+    )?;
+    get(conn, template, file)
+}
+
+/// Adds a custom script, or replaces the source of an existing one. Bundled scripts are
+/// changed through the overrides directory instead.
+pub fn add(
+    conn: &Connection,
+    template: &Template,
+    file: &str,
+    source: &str,
+) -> Result<UserScript, AppError> {
+    validate_file_name(file)?;
+    if is_bundled(file) {
+        return Err(AppError::Validation(format!(
+            "{} is a bundled script; put a copy in the '{}' directory to override it",
+            file,
+            template::OVERRIDES_DIR
+        )));
+    }
+    for pattern in template::read_matches(source.as_bytes()) {
+        validate_pattern(&pattern)?;
+    }
+
+    fs::create_dir_all(template.dir.join(CUSTOM_DIR))?;
+    fs::write(custom_path(template, file), source)?;
+    get(conn, template, file)
+}
+
+/// Deletes a custom script and its settings.
+pub fn remove(conn: &Connection, template: &Template, file: &str) -> Result<(), AppError> {
+    if is_bundled(file) {
+        return Err(AppError::Validation(format!(
+            "{} is a bundled script and can only be disabled",
+            file
+        )));
+    }
+    validate_file_name(file)?;
+    fs::remove_file(custom_path(template, file))
+        .map_err(|_| AppError::NotFound(format!("Script not found: {}", file)))?;
+    conn.execute("DELETE FROM user_scripts WHERE file = ?1", [file])?;
+    Ok(())
+}
+
+/// Wraps a script so it only runs on pages matching `patterns`. Like a userscript
+/// manager, each script gets its own function scope.
+fn guard(patterns: &[String], source: &str) -> String {
+    let regexes: Vec<String> = patterns.iter().map(|p| pattern_regex(p)).collect();
+    format!(
+        ";(function () {{\n  if (!{}.some((re) => new RegExp(re).test(location.href))) return;\n{}\n}})();\n",
+        serde_json::to_string(&regexes).unwrap_or_else(|_| "[]".to_string()),
+        source
+    )
+}
+
+/// Initialization scripts for the main webview: every enabled script, guarded by its
+/// match patterns.
+pub fn injections(conn: &Connection, template: &Template) -> Result<Vec<String>, AppError> {
+    Ok(list(conn, template)?
+        .into_iter()
+        .filter(|script| script.enabled)
+        .filter_map(|script| match read_source(template, &script.file) {
+            Ok((_, source)) => Some(guard(&script.effective_matches(), &source)),
+            Err(e) => {
+                warn!("[scripts] Skipping {}: {}", script.file, e);
+                None
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::Regex;
+
+    fn setup() -> (Connection, Template) {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(include_str!("../migrations/001_init.sql"))
+            .unwrap();
+        let dir = std::env::temp_dir().join(format!("aitodo-scripts-{}", uuid::Uuid::new_v4()));
+        (conn, Template::new(dir))
+    }
+
+    fn matches_url(patterns: &[String], url: &str) -> bool {
+        patterns
+            .iter()
+            .any(|pattern| Regex::new(&pattern_regex(pattern)).is_ok_and(|re| re.is_match(url)))
+    }
+
+    #[test]
+    fn test_match_patterns() {
+        let chatgpt = vec!["https://chatgpt.com/*".to_string()];
+        assert!(matches_url(&chatgpt, "https://chatgpt.com/c/123?model=gpt"));
+        assert!(!matches_url(&chatgpt, "https://auth.openai.com/log-in"));
+        assert!(!matches_url(&chatgpt, "https://chatgpt.com.evil.io/"));
+        assert!(matches_url(&[MATCH_ALL.to_string()], "about:blank"));
+        assert!(validate_pattern("https://*.openai.com/*").is_ok());
+        assert_eq!(
+            validate_pattern("https://a b").unwrap_err().code(),
+            "Validation"
+        );
+    }
+
+    #[test]
+    fn test_registry_lists_and_configures_scripts() {
+        let (conn, template) = setup();
+        let source = "// ==UserScript==\n// @name Wide mode\n// @version 1.0.0\n// @match https://chatgpt.com/c/*\n// ==/UserScript==\ndocument.body.classList.add('wide');\n";
+        add(&conn, &template, "wide.js", source).unwrap();
+
+        let scripts = list(&conn, &template).unwrap();
+        let files: Vec<&str> = scripts.iter().map(|s| s.file.as_str()).collect();
+        assert_eq!(files, ["ask.js", "deep_research.js", "wide.js"]);
+        assert_eq!(scripts[0].origin, ScriptOrigin::Bundled);
+        assert_eq!(scripts[2].name, "Wide mode");
+        assert_eq!(scripts[2].origin, ScriptOrigin::Custom);
+        assert_eq!(scripts[2].effective_matches(), ["https://chatgpt.com/c/*"]);
+        assert!(scripts.iter().all(|s| s.enabled));
+
+        set_enabled(&conn, &template, "ask.js", false).unwrap();
+        let wide = set_matches(
+            &conn,
+            &template,
+            "wide.js",
+            Some(vec!["https://chatgpt.com/*".to_string()]),
+        )
+        .unwrap();
+        assert_eq!(wide.effective_matches(), ["https://chatgpt.com/*"]);
+        assert_eq!(
+            set_matches(&conn, &template, "wide.js", Some(vec![]))
+                .unwrap_err()
+                .code(),
+            "Validation"
+        );
+
+        let injected = injections(&conn, &template).unwrap();
+        assert_eq!(injected.len(), 2);
+        assert!(injected[0].contains("window.DeepResearch = DeepResearch"));
+        assert!(injected[1].contains(r#"["^https://chatgpt\\.com/.*$"]"#));
+
+        remove(&conn, &template, "wide.js").unwrap();
+        assert_eq!(list(&conn, &template).unwrap().len(), 2);
+        assert_eq!(
+            remove(&conn, &template, "ask.js").unwrap_err().code(),
+            "Validation"
+        );
+        assert_eq!(
+            set_enabled(&conn, &template, "gone.js", true)
+                .unwrap_err()
+                .code(),
+            "NotFound"
+        );
+
+        fs::remove_dir_all(&template.dir).unwrap();
+    }
+
+    #[test]
+    fn test_add_rejects_unsafe_names() {
+        let (conn, template) = setup();
+        for file in ["../evil.js", "ask.js", "notes.txt", ".hidden.js"] {
+            assert_eq!(
+                add(&conn, &template, file, "// x").unwrap_err().code(),
+                "Validation",
+                "{}",
+                file
+            );
+        }
+        fs::remove_dir_all(&template.dir).unwrap();
+    }
+}
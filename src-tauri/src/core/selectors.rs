@@ -0,0 +1,260 @@
+use log::{info, warn};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::Path};
+use tauri::{AppHandle, Manager};
+
+use crate::core::error::AppError;
+
+pub static SELECTOR_PACK: &[u8] = include_bytes!("../../scripts/selectors.json");
+
+/// File name of the active selector pack in the scripts directory.
+pub static PACK_FILE: &str = "selectors.json";
+
+/// Every element `deep_research.js` looks up. A pack must define all of them.
+pub static SELECTOR_KEYS: [&str; 7] = [
+    "inputBox",
+    "sendButton",
+    "deepResearchButton",
+    "responseContainer",
+    "thinkingIndicator",
+    "loginButton",
+    "userMenu",
+];
+
+/// Pseudo-classes from test tools and jQuery that `querySelector` rejects.
+const UNSUPPORTED_PSEUDO: [&str; 3] = [":has-text(", ":contains(", ":text("];
+
+/// The DOM selectors the ChatGPT automation uses, versioned so packs can be updated
+/// without a new build. Each key maps to a fallback chain tried in order.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SelectorPack {
+    pub name: String,
+    pub version: String,
+    pub selectors: BTreeMap<String, Vec<String>>,
+}
+
+/// Rejects selectors that would throw in `querySelector`, as far as that can be told
+/// without a DOM: empty ones, unbalanced brackets or quotes and unsupported
+/// pseudo-classes.
+pub fn check_selector(selector: &str) -> Result<(), String> {
+    if selector.trim().is_empty() {
+        return Err("empty selector".to_string());
+    }
+    if let Some(pseudo) = UNSUPPORTED_PSEUDO.iter().find(|p| selector.contains(*p)) {
+        return Err(format!(
+            "'{}' is not supported by browsers",
+            pseudo.trim_end_matches('(')
+        ));
+    }
+
+    let mut open: Vec<char> = Vec::new();
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for c in selector.chars() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match (quote, c) {
+            (_, '\\') => escaped = true,
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(' | '[') => open.push(c),
+            (None, ')') if open.pop() != Some('(') => return Err("unbalanced ')'".to_string()),
+            (None, ']') if open.pop() != Some('[') => return Err("unbalanced ']'".to_string()),
+            _ => {}
+        }
+    }
+    if quote.is_some() {
+        return Err("unterminated string".to_string());
+    }
+    if let Some(c) = open.last() {
+        return Err(format!("unclosed '{}'", c));
+    }
+    Ok(())
+}
+
+impl SelectorPack {
+    pub fn validate(&self) -> Result<(), AppError> {
+        let invalid = |message: String| {
+            Err(AppError::Validation(format!(
+                "Invalid selector pack '{}': {}",
+                self.name, message
+            )))
+        };
+
+        if Version::parse(&self.version).is_err() {
+            return invalid(format!("version '{}' is not semver", self.version));
+        }
+        if let Some(key) = SELECTOR_KEYS
+            .iter()
+            .find(|key| !self.selectors.contains_key(**key))
+        {
+            return invalid(format!("missing '{}'", key));
+        }
+        for (key, chain) in &self.selectors {
+            if !SELECTOR_KEYS.contains(&key.as_str()) {
+                return invalid(format!("unknown key '{}'", key));
+            }
+            if chain.is_empty() {
+                return invalid(format!("'{}' has no selectors", key));
+            }
+            for selector in chain {
+                if let Err(reason) = check_selector(selector) {
+                    return invalid(format!("'{}' selector {:?}: {}", key, selector, reason));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, AppError> {
+        let pack: SelectorPack = serde_json::from_str(contents)
+            .map_err(|e| AppError::Validation(format!("Invalid selector pack: {}", e)))?;
+        pack.validate()?;
+        Ok(pack)
+    }
+
+    /// The pack shipped with the app.
+    pub fn bundled() -> Self {
+        Self::parse(&String::from_utf8_lossy(SELECTOR_PACK)).expect("bundled selector pack")
+    }
+
+    fn semver(&self) -> Option<Version> {
+        Version::parse(&self.version).ok()
+    }
+
+    /// Script defining the pack for `deep_research.js`.
+    pub fn init_script(&self) -> String {
+        format!(
+            "window.__AITODO_SELECTORS__ = {};",
+            serde_json::to_string(self).unwrap_or_else(|_| "null".to_string())
+        )
+    }
+}
+
+/// The pack in `dir`, if there is a valid one.
+pub fn read(dir: &Path) -> Result<SelectorPack, AppError> {
+    SelectorPack::parse(&fs::read_to_string(dir.join(PACK_FILE))?)
+}
+
+/// Makes sure `dir` holds a valid pack at least as new as the bundled one and returns it.
+/// A newer pack loaded by the user is kept.
+pub fn install(dir: &Path) -> Result<SelectorPack, AppError> {
+    let bundled = SelectorPack::bundled();
+    match read(dir) {
+        Ok(pack) if pack.semver() >= bundled.semver() => return Ok(pack),
+        Ok(pack) => info!("[selectors] {} → {}", pack.version, bundled.version),
+        Err(AppError::NotFound(_)) => {}
+        Err(e) => warn!("[selectors] Replacing the selector pack: {}", e),
+    }
+    fs::create_dir_all(dir)?;
+    fs::write(dir.join(PACK_FILE), SELECTOR_PACK)?;
+    Ok(bundled)
+}
+
+/// Validates the pack at `path` and makes it the active one in `dir`.
+pub fn load_file(dir: &Path, path: &Path) -> Result<SelectorPack, AppError> {
+    let contents = fs::read_to_string(path)?;
+    let pack = SelectorPack::parse(&contents)?;
+    fs::create_dir_all(dir)?;
+    fs::write(dir.join(PACK_FILE), contents)?;
+    info!("[selectors] Loaded {} {}", pack.name, pack.version);
+    Ok(pack)
+}
+
+/// Hands `pack` to the running automation, so it applies without a reload.
+pub fn apply(app: &AppHandle, pack: &SelectorPack) -> Result<(), AppError> {
+    let webview = app
+        .get_window("core")
+        .and_then(|win| win.get_webview("main"))
+        .ok_or_else(|| AppError::Webview("Main webview is not available".to_string()))?;
+    webview.eval(&pack.init_script())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("aitodo-selectors-{}", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn test_check_selector() {
+        assert!(check_selector(r#"textarea[placeholder*="Message (Enter)"]"#).is_ok());
+        assert!(check_selector(r#"[aria-label='a\'b']"#).is_ok());
+        assert!(check_selector("button:has-text(\"Deep Research\")").is_err());
+        assert!(check_selector("[data-testid=\"send\"").is_err());
+        assert!(check_selector("div:not(.a))").is_err());
+        assert!(check_selector("[title=\"open]").is_err());
+        assert!(check_selector("  ").is_err());
+    }
+
+    #[test]
+    fn test_validate_pack() {
+        let bundled = SelectorPack::bundled();
+        assert_eq!(bundled.selectors.len(), SELECTOR_KEYS.len());
+
+        let mut pack = bundled.clone();
+        pack.selectors.remove("sendButton");
+        assert!(pack
+            .validate()
+            .unwrap_err()
+            .message()
+            .contains("sendButton"));
+
+        let mut pack = bundled.clone();
+        pack.selectors
+            .insert("sendButon".to_string(), vec!["button".to_string()]);
+        assert!(pack
+            .validate()
+            .unwrap_err()
+            .message()
+            .contains("unknown key"));
+
+        let mut pack = bundled.clone();
+        pack.selectors.insert("userMenu".to_string(), vec![]);
+        assert_eq!(pack.validate().unwrap_err().code(), "Validation");
+
+        let mut pack = bundled;
+        pack.version = "2024-06".to_string();
+        assert_eq!(pack.validate().unwrap_err().code(), "Validation");
+        assert_eq!(
+            SelectorPack::parse("{\"name\": 1}").unwrap_err().code(),
+            "Validation"
+        );
+    }
+
+    #[test]
+    fn test_install_and_load() {
+        let dir = temp_dir();
+        assert_eq!(install(&dir).unwrap(), SelectorPack::bundled());
+
+        // A newer pack loaded from disk is active and survives the next start.
+        let mut newer = SelectorPack::bundled();
+        newer.version = "9.0.0".to_string();
+        newer.selectors.insert(
+            "sendButton".to_string(),
+            vec!["#composer-submit".to_string()],
+        );
+        let download = dir.join("download.json");
+        fs::write(&download, serde_json::to_string(&newer).unwrap()).unwrap();
+        assert_eq!(load_file(&dir, &download).unwrap(), newer);
+        assert_eq!(install(&dir).unwrap(), newer);
+
+        // An invalid pack is rejected and leaves the active one alone.
+        fs::write(&download, "{\"name\": \"broken\"}").unwrap();
+        assert!(load_file(&dir, &download).is_err());
+        assert_eq!(read(&dir).unwrap(), newer);
+
+        // A corrupt file is replaced by the bundled pack.
+        fs::write(dir.join(PACK_FILE), "not json").unwrap();
+        assert_eq!(install(&dir).unwrap(), SelectorPack::bundled());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,157 @@
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{sync::mpsc, time::Duration};
+use tauri::{AppHandle, Listener, Manager};
+
+use crate::core::error::AppError;
+
+/// Event the main webview answers a self-test with.
+pub static SELFTEST_EVENT: &str = "automation_selftest";
+
+/// How long to wait for the main webview to answer.
+const SELFTEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Elements research cannot start without. The others are often missing on a page that is
+/// still usable: the send button only shows once there is text to send, and there are no
+/// responses in a new chat.
+const REQUIRED: [&str; 1] = ["inputBox"];
+
+/// Asks `deep_research.js` for a report, or reports that it is missing.
+const SELFTEST_SCRIPT: &str = r#"
+(function () {
+  const report = window.DeepResearch
+    ? window.DeepResearch.selfTest()
+    : { url: location.href, script_loaded: false, logged_in: false, pack_version: null, checks: [] };
+  window.__TAURI__.event.emit('automation_selftest', report);
+})();
+"#;
+
+/// Whether one element was found, and by which selector of its fallback chain.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SelftestCheck {
+    /// Key in the selector pack.
+    pub key: String,
+    pub found: bool,
+    pub selector: Option<String>,
+}
+
+/// What the automation found on the ChatGPT page.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SelftestReport {
+    pub url: String,
+    /// Whether `deep_research.js` is loaded in the page.
+    pub script_loaded: bool,
+    pub logged_in: bool,
+    pub pack_version: Option<String>,
+    pub checks: Vec<SelftestCheck>,
+    /// Problems that keep research from starting; empty when it can run.
+    #[serde(default)]
+    pub problems: Vec<String>,
+}
+
+impl SelftestReport {
+    fn find_problems(&self) -> Vec<String> {
+        if !self.script_loaded {
+            return vec!["The automation script is not loaded in the ChatGPT page".to_string()];
+        }
+        let mut problems = Vec::new();
+        if !self.logged_in {
+            problems.push("Not logged in to ChatGPT".to_string());
+        }
+        for check in &self.checks {
+            if REQUIRED.contains(&check.key.as_str()) && !check.found {
+                problems.push(format!("No element matches the '{}' selectors", check.key));
+            }
+        }
+        problems
+    }
+
+    pub fn passed(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    /// Fails with the problems found, so research is not started on a broken page.
+    pub fn ensure_passed(&self) -> Result<(), AppError> {
+        if self.passed() {
+            return Ok(());
+        }
+        Err(AppError::Provider(format!(
+            "Automation self-test failed: {}",
+            self.problems.join("; ")
+        )))
+    }
+}
+
+/// Parses the page's answer and works out its problems.
+pub fn parse_report(payload: &str) -> Result<SelftestReport, AppError> {
+    let mut report: SelftestReport = serde_json::from_str(payload)?;
+    report.problems = report.find_problems();
+    Ok(report)
+}
+
+/// Runs the self-test in the main webview and waits for its report. Blocks, so it must
+/// not run on the main thread.
+pub fn run(app: &AppHandle) -> Result<SelftestReport, AppError> {
+    let webview = app
+        .get_window("core")
+        .and_then(|win| win.get_webview("main"))
+        .ok_or_else(|| AppError::Webview("Main webview is not available".to_string()))?;
+
+    let (tx, rx) = mpsc::channel();
+    let listener = app.once(SELFTEST_EVENT, move |event| {
+        let _ = tx.send(event.payload().to_string());
+    });
+    if let Err(e) = webview.eval(SELFTEST_SCRIPT) {
+        app.unlisten(listener);
+        return Err(e.into());
+    }
+    let payload = rx.recv_timeout(SELFTEST_TIMEOUT).map_err(|_| {
+        app.unlisten(listener);
+        AppError::Provider("The ChatGPT page did not answer the self-test".to_string())
+    })?;
+
+    let report = parse_report(&payload)?;
+    if report.passed() {
+        info!("[selftest] Passed on {}", report.url);
+    } else {
+        warn!("[selftest] {}", report.problems.join("; "));
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_report() {
+        let healthy = r##"{
+            "url": "https://chatgpt.com/",
+            "script_loaded": true,
+            "logged_in": true,
+            "pack_version": "1.0.0",
+            "checks": [
+                {"key": "inputBox", "found": true, "selector": "#prompt-textarea"},
+                {"key": "sendButton", "found": false, "selector": null}
+            ]
+        }"##;
+        let report = parse_report(healthy).unwrap();
+        assert!(report.passed());
+        assert!(report.ensure_passed().is_ok());
+
+        let broken = healthy
+            .replace(r#""logged_in": true"#, r#""logged_in": false"#)
+            .replace(
+                r##"{"key": "inputBox", "found": true, "selector": "#prompt-textarea"}"##,
+                r#"{"key": "inputBox", "found": false, "selector": null}"#,
+            );
+        let report = parse_report(&broken).unwrap();
+        assert_eq!(report.problems.len(), 2);
+        let err = report.ensure_passed().unwrap_err();
+        assert_eq!(err.code(), "Provider");
+        assert!(err.message().contains("inputBox"));
+
+        let missing = r#"{"url": "about:blank", "script_loaded": false, "logged_in": false, "pack_version": null, "checks": []}"#;
+        assert_eq!(parse_report(missing).unwrap().problems.len(), 1);
+    }
+}
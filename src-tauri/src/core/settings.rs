@@ -0,0 +1,472 @@
+use chrono::Utc;
+use log::{error, info, warn};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::{fs, path::Path};
+use tauri::{AppHandle, Emitter};
+
+use crate::core::{conf::AppConf, db, error::AppError};
+
+/// Event broadcast to every window after settings change.
+pub static SETTINGS_CHANGED: &str = "settings_changed";
+
+/// Version of the settings schema. Bump it and append a step to [`UPGRADES`] whenever a
+/// stored value changes meaning.
+pub const SETTINGS_VERSION: u32 = 1;
+
+/// Step upgrades; entry `n` turns version `n` values into version `n + 1` values.
+const UPGRADES: [fn(&mut Map<String, Value>); SETTINGS_VERSION as usize] = [upgrade_v0];
+
+/// Unversioned `config.json` files. Older builds treated a snapshot interval or
+/// retention of `0` as `1`, which is now rejected.
+fn upgrade_v0(values: &mut Map<String, Value>) {
+    for key in ["snapshot_interval_hours", "snapshot_retention"] {
+        if values.get(key).and_then(Value::as_u64) == Some(0) {
+            values.insert(key.to_string(), Value::from(1));
+        }
+    }
+}
+
+/// Brings `values` up to [`SETTINGS_VERSION`] one step at a time and returns the version
+/// they started at; values without a version are version `0`. Values written by a newer
+/// build are left as they are, and their unknown keys are ignored when read.
+pub fn upgrade(values: &mut Map<String, Value>) -> u32 {
+    let from = values
+        .get("version")
+        .and_then(Value::as_u64)
+        .map_or(0, |v| v as u32);
+    if from > SETTINGS_VERSION {
+        warn!(
+            "[settings] Settings version {} is newer than {}",
+            from, SETTINGS_VERSION
+        );
+        return from;
+    }
+
+    for step in &UPGRADES[from as usize..] {
+        step(values);
+    }
+    values.insert("version".to_string(), Value::from(SETTINGS_VERSION));
+    from
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsChanged {
+    pub keys: Vec<String>,
+    pub settings: AppConf,
+}
+
+/// Every setting key with its default value. [`AppConf`] is the schema; each field is
+/// stored as one JSON-encoded row of the `settings` table.
+pub fn defaults() -> Map<String, Value> {
+    match serde_json::to_value(AppConf::new()) {
+        Ok(Value::Object(values)) => values,
+        _ => Map::new(),
+    }
+}
+
+/// Rules that go beyond the type of a field.
+fn check(key: &str, conf: &AppConf) -> Result<(), AppError> {
+    let invalid = |reason: &str| {
+        Err(AppError::Validation(format!(
+            "Invalid value for '{}': {}",
+            key, reason
+        )))
+    };
+    match key {
+        "theme" if !["light", "dark", "system"].contains(&conf.theme.as_str()) => {
+            invalid("expected light, dark or system")
+        }
+        "snapshot_interval_hours" if conf.snapshot_interval_hours == 0 => {
+            invalid("must be at least one hour")
+        }
+        "snapshot_retention" if conf.snapshot_retention == 0 => invalid("must keep at least one"),
+        "notifications" => conf.notifications.validate(),
+        _ => Ok(()),
+    }
+}
+
+/// Validates `value` for `key` on top of `values` and returns the resulting settings.
+fn validate(values: &Map<String, Value>, key: &str, value: Value) -> Result<AppConf, AppError> {
+    if !values.contains_key(key) {
+        return Err(AppError::Validation(format!("Unknown setting: {}", key)));
+    }
+
+    let mut merged = values.clone();
+    merged.insert(key.to_string(), value);
+    let conf: AppConf = serde_json::from_value(Value::Object(merged))
+        .map_err(|e| AppError::Validation(format!("Invalid value for '{}': {}", key, e)))?;
+    check(key, &conf)?;
+    Ok(conf)
+}
+
+fn stored(conn: &Connection) -> Result<Vec<(String, Option<String>)>, AppError> {
+    let mut stmt = conn.prepare("SELECT key, value FROM settings ORDER BY key")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    Ok(rows.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Reads the settings, using the default for every key that is missing or no longer
+/// valid. Rows owned by other modules, such as the status rules, are ignored.
+pub fn read(conn: &Connection) -> Result<AppConf, AppError> {
+    let mut values = defaults();
+    for (key, value) in stored(conn)? {
+        if !values.contains_key(&key) {
+            continue;
+        }
+
+        let checked = match value.as_deref().map(serde_json::from_str::<Value>) {
+            Some(Ok(value)) => validate(&values, &key, value.clone()).map(|_| value),
+            Some(Err(e)) => Err(e.into()),
+            None => Err(AppError::Validation("value is empty".to_string())),
+        };
+        match checked {
+            Ok(value) => {
+                values.insert(key, value);
+            }
+            Err(e) => error!("[settings] Ignoring stored '{}': {}", key, e),
+        }
+    }
+    Ok(serde_json::from_value(Value::Object(values))?)
+}
+
+fn current(conn: &Connection) -> Result<Map<String, Value>, AppError> {
+    match serde_json::to_value(read(conn)?)? {
+        Value::Object(values) => Ok(values),
+        _ => Ok(defaults()),
+    }
+}
+
+/// Validates and stores `changes` in one transaction; nothing is written if any key is
+/// rejected.
+pub fn write(conn: &mut Connection, changes: Map<String, Value>) -> Result<AppConf, AppError> {
+    let mut values = current(conn)?;
+    for (key, value) in &changes {
+        validate(&values, key, value.clone())?;
+        values.insert(key.clone(), value.clone());
+    }
+
+    let tx = conn.transaction()?;
+    for (key, value) in &changes {
+        tx.execute(
+            "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+            params![key, serde_json::to_string(value)?],
+        )?;
+    }
+    tx.commit()?;
+
+    Ok(serde_json::from_value(Value::Object(values))?)
+}
+
+/// Copies the values of a legacy `config.json` that are valid and not stored yet.
+/// Returns the imported keys.
+pub fn import_legacy(conn: &mut Connection, contents: &str) -> Result<Vec<String>, AppError> {
+    let Value::Object(mut legacy) = serde_json::from_str(contents)? else {
+        return Err(AppError::Validation(
+            "config.json is not a JSON object".to_string(),
+        ));
+    };
+    upgrade(&mut legacy);
+
+    let existing: Vec<String> = stored(conn)?.into_iter().map(|(key, _)| key).collect();
+    let mut values = current(conn)?;
+    let mut changes = Map::new();
+    for (key, value) in legacy {
+        // The store keeps its own version.
+        if key == "version" || existing.contains(&key) {
+            continue;
+        }
+        match validate(&values, &key, value.clone()) {
+            Ok(_) => {
+                values.insert(key.clone(), value.clone());
+                changes.insert(key, value);
+            }
+            Err(e) => error!("[settings] Skipping legacy '{}': {}", key, e),
+        }
+    }
+
+    let keys = changes.keys().cloned().collect();
+    write(conn, changes)?;
+    Ok(keys)
+}
+
+pub fn load() -> Result<AppConf, AppError> {
+    let conn = db::reader()?;
+    read(&conn)
+}
+
+/// Stores `changes`, a JSON object of setting keys, and broadcasts the new settings.
+pub fn update(app: &AppHandle, changes: Value) -> Result<AppConf, AppError> {
+    let Value::Object(changes) = changes else {
+        return Err(AppError::Validation(
+            "Settings must be a JSON object".to_string(),
+        ));
+    };
+
+    if changes.contains_key("version") {
+        return Err(AppError::Validation(
+            "The settings version is managed by the app".to_string(),
+        ));
+    }
+
+    let keys: Vec<String> = changes.keys().cloned().collect();
+    let settings = write(&mut db::writer(), changes)?;
+
+    let payload = SettingsChanged {
+        keys,
+        settings: settings.clone(),
+    };
+    if let Err(e) = app.emit(SETTINGS_CHANGED, payload) {
+        error!("[settings] Failed to broadcast change: {}", e);
+    }
+    Ok(settings)
+}
+
+/// Upgrades the stored settings to [`SETTINGS_VERSION`]. Stores without a version row
+/// predate versioning, which started at version 1.
+pub fn upgrade_stored(conn: &mut Connection) -> Result<u32, AppError> {
+    let mut values = Map::new();
+    for (key, value) in stored(conn)? {
+        if let Some(Ok(value)) = value.as_deref().map(serde_json::from_str::<Value>) {
+            values.insert(key, value);
+        }
+    }
+    values
+        .entry("version")
+        .or_insert_with(|| Value::from(SETTINGS_VERSION));
+
+    let before = values.clone();
+    let from = upgrade(&mut values);
+
+    let tx = conn.transaction()?;
+    for (key, value) in &values {
+        if key == "version" || before.get(key) != Some(value) {
+            tx.execute(
+                "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+                params![key, serde_json::to_string(value)?],
+            )?;
+        }
+    }
+    tx.commit()?;
+    Ok(from)
+}
+
+/// Imports a legacy `config.json` and keeps it as `config.json.migrated`. A file that
+/// cannot be parsed is left in place, so it can be fixed by hand and imported on the next
+/// launch, and a copy is kept as `config.json.corrupt-<timestamp>` in case it gets lost.
+pub fn import_legacy_file(conn: &mut Connection, path: &Path) -> Result<Vec<String>, AppError> {
+    let contents = fs::read_to_string(path)?;
+    match import_legacy(conn, &contents) {
+        Ok(keys) => {
+            fs::rename(path, path.with_extension("json.migrated"))?;
+            Ok(keys)
+        }
+        Err(AppError::Validation(e)) => {
+            error!("[settings] Cannot import {}: {}", path.display(), e);
+            if !has_quarantined_copy(path, &contents)? {
+                let quarantine = path.with_extension(format!(
+                    "json.corrupt-{}",
+                    Utc::now().format("%Y%m%d%H%M%S")
+                ));
+                fs::copy(path, &quarantine)?;
+                info!("[settings] Kept a copy as {}", quarantine.display());
+            }
+            Ok(Vec::new())
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Whether a quarantined copy of `path` with the same `contents` already exists, so an
+/// unfixed file is not copied again on every launch.
+fn has_quarantined_copy(path: &Path, contents: &str) -> Result<bool, AppError> {
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+        return Ok(false);
+    };
+    let prefix = format!("{}.corrupt-", name.to_string_lossy());
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_name().to_string_lossy().starts_with(&prefix)
+            && fs::read_to_string(entry.path()).is_ok_and(|copy| copy == contents)
+        {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Upgrades the settings store and moves the legacy `config.json` into it once.
+pub fn migrate(app: &AppHandle) -> Result<(), AppError> {
+    let from = upgrade_stored(&mut db::writer())?;
+    if from < SETTINGS_VERSION {
+        info!(
+            "[settings] Upgraded settings from version {} to {}",
+            from, SETTINGS_VERSION
+        );
+    }
+
+    let path = AppConf::get_conf_path(app)?;
+    if path.exists() {
+        let keys = import_legacy_file(&mut db::writer(), &path)?;
+        if !keys.is_empty() {
+            info!("[settings] Migrated {} from config.json", keys.join(", "));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn open_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(include_str!("../migrations/001_init.sql"))
+            .unwrap();
+        conn
+    }
+
+    fn changes(value: Value) -> Map<String, Value> {
+        match value {
+            Value::Object(values) => values,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_defaults_and_stored_values() {
+        let mut conn = open_db();
+        let conf = read(&conn).unwrap();
+        assert_eq!(conf.theme, "system");
+        assert_eq!(conf.trash_retention_days, 30);
+
+        let conf = write(
+            &mut conn,
+            changes(json!({"theme": "dark", "stay_on_top": true})),
+        )
+        .unwrap();
+        assert_eq!(conf.theme, "dark");
+
+        // Rows from other modules and invalid stored values fall back to defaults.
+        conn.execute_batch(
+            "INSERT INTO settings (key, value) VALUES ('status_rules', '[]');
+             UPDATE settings SET value = '\"purple\"' WHERE key = 'theme';",
+        )
+        .unwrap();
+        let conf = read(&conn).unwrap();
+        assert_eq!(conf.theme, "system");
+        assert!(conf.stay_on_top);
+    }
+
+    #[test]
+    fn test_write_validates_every_key() {
+        let mut conn = open_db();
+        let rejected = [
+            json!({"unknown": 1}),
+            json!({"stay_on_top": "yes"}),
+            json!({"theme": "purple"}),
+            json!({"snapshot_retention": 0}),
+            json!({"ask_mode": true, "notifications": {"quiet_hours": {"start": "22", "end": "07:00"}}}),
+        ];
+        for value in rejected {
+            let err = write(&mut conn, changes(value)).unwrap_err();
+            assert_eq!(err.code(), "Validation");
+        }
+        // Rejected batches are not partially written.
+        assert!(!read(&conn).unwrap().ask_mode);
+    }
+
+    #[test]
+    fn test_import_legacy_keeps_stored_values() {
+        let mut conn = open_db();
+        write(&mut conn, changes(json!({"theme": "light"}))).unwrap();
+
+        let legacy =
+            r#"{"theme": "dark", "ask_mode": true, "snapshot_retention": "many", "removed": 1}"#;
+        let imported = import_legacy(&mut conn, legacy).unwrap();
+        assert_eq!(imported, vec!["ask_mode".to_string()]);
+
+        let conf = read(&conn).unwrap();
+        assert_eq!(conf.theme, "light");
+        assert!(conf.ask_mode);
+        assert_eq!(conf.snapshot_retention, 10);
+
+        assert_eq!(
+            import_legacy(&mut conn, "{ not json").unwrap_err().code(),
+            "Validation"
+        );
+    }
+
+    #[test]
+    fn test_upgrade_old_current_and_newer_values() {
+        let mut old = changes(json!({"theme": "dark", "snapshot_retention": 0}));
+        assert_eq!(upgrade(&mut old), 0);
+        assert_eq!(old["snapshot_retention"], json!(1));
+        assert_eq!(old["version"], json!(SETTINGS_VERSION));
+
+        let mut current = changes(json!({"version": SETTINGS_VERSION, "snapshot_retention": 3}));
+        let expected = current.clone();
+        assert_eq!(upgrade(&mut current), SETTINGS_VERSION);
+        assert_eq!(current, expected);
+
+        let mut newer = changes(json!({"version": 99, "theme": "dark", "layout": "grid"}));
+        let expected = newer.clone();
+        assert_eq!(upgrade(&mut newer), 99);
+        assert_eq!(newer, expected);
+    }
+
+    #[test]
+    fn test_upgrade_stored_settings() {
+        let mut conn = open_db();
+        assert_eq!(upgrade_stored(&mut conn).unwrap(), SETTINGS_VERSION);
+        assert_eq!(read(&conn).unwrap().version, SETTINGS_VERSION);
+
+        conn.execute_batch(
+            "UPDATE settings SET value = '0' WHERE key = 'version';
+             INSERT INTO settings (key, value) VALUES ('snapshot_interval_hours', '0');",
+        )
+        .unwrap();
+        assert_eq!(upgrade_stored(&mut conn).unwrap(), 0);
+        assert_eq!(read(&conn).unwrap().snapshot_interval_hours, 1);
+    }
+
+    #[test]
+    fn test_legacy_files_are_migrated_or_quarantined() {
+        let dir = std::env::temp_dir().join(format!("aitodo-settings-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.json");
+        let mut conn = open_db();
+
+        fs::write(&path, r#"{"theme": "dark", "snapshot_retention": 0}"#).unwrap();
+        let mut imported = import_legacy_file(&mut conn, &path).unwrap();
+        imported.sort();
+        assert_eq!(imported, vec!["snapshot_retention", "theme"]);
+        assert!(!path.exists());
+        assert!(dir.join("config.json.migrated").exists());
+        assert_eq!(read(&conn).unwrap().snapshot_retention, 1);
+
+        // A corrupt file stays where it is and is copied aside once.
+        let corrupt = r#"{"theme": "dark""#;
+        fs::write(&path, corrupt).unwrap();
+        for _ in 0..2 {
+            assert!(import_legacy_file(&mut conn, &path).unwrap().is_empty());
+            assert_eq!(fs::read_to_string(&path).unwrap(), corrupt);
+        }
+        let quarantined: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with("config.json.corrupt-")
+            })
+            .collect();
+        assert_eq!(quarantined.len(), 1);
+        assert_eq!(fs::read_to_string(quarantined[0].path()).unwrap(), corrupt);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,9 +1,13 @@
+use log::error;
 use std::{
     path::PathBuf,
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
 };
 use tauri::{
-    webview::DownloadEvent, App, LogicalPosition, Manager, PhysicalSize, WebviewBuilder,
+    webview::DownloadEvent, App, Listener, LogicalPosition, Manager, PhysicalSize, WebviewBuilder,
     WebviewUrl, WindowBuilder, WindowEvent,
 };
 use tauri_plugin_opener::OpenerExt;
@@ -14,21 +18,45 @@ use tauri::TitleBarStyle;
 use crate::core::{
     conf::AppConf,
     constant::{ASK_HEIGHT, INIT_SCRIPT, TITLEBAR_HEIGHT},
-    template,
+    db, scripts,
+    selectors::{self, SelectorPack},
+    settings::{self, SettingsChanged, SETTINGS_CHANGED},
+    template, window,
 };
 
+/// Whether the ask bar is shown. Window events read it on every resize, so it is cached
+/// here and kept current from the settings broadcast.
+static ASK_MODE: AtomicBool = AtomicBool::new(false);
+
 pub fn init(app: &mut App) -> Result<(), Box<dyn std::error::Error>> {
     let handle = app.handle();
 
-    let conf = &AppConf::load(handle)?;
+    let conf = &settings::load()?;
     let ask_mode_height = if conf.ask_mode { ASK_HEIGHT } else { 0.0 };
+    let stay_on_top = conf.stay_on_top;
 
-    template::Template::new(AppConf::get_scripts_path(handle)?);
+    ASK_MODE.store(conf.ask_mode, Ordering::Relaxed);
+    handle.listen(SETTINGS_CHANGED, |event| {
+        match serde_json::from_str::<SettingsChanged>(event.payload()) {
+            Ok(changed) => ASK_MODE.store(changed.settings.ask_mode, Ordering::Relaxed),
+            Err(e) => error!("[settings] Malformed change broadcast: {}", e),
+        }
+    });
+
+    let template = template::Template::new(AppConf::get_scripts_path(handle)?);
+    let selector_pack = selectors::install(&template.dir).unwrap_or_else(|e| {
+        error!("[selectors] {}", e);
+        SelectorPack::bundled()
+    });
+    let user_scripts = {
+        let conn = db::reader()?;
+        scripts::injections(&conn, &template)?
+    };
 
     tauri::async_runtime::spawn({
         let handle = handle.clone();
         async move {
-            let mut core_window = WindowBuilder::new(&handle, "core").title("ChatGPT");
+            let mut core_window = WindowBuilder::new(&handle, "core").title("AI Todo");
 
             #[cfg(target_os = "macos")]
             {
@@ -41,7 +69,8 @@ pub fn init(app: &mut App) -> Result<(), Box<dyn std::error::Error>> {
                 .resizable(true)
                 .inner_size(800.0, 600.0)
                 .min_inner_size(300.0, 200.0)
-                .theme(Some(AppConf::get_theme(&handle)));
+                .always_on_top(stay_on_top)
+                .theme(AppConf::get_theme());
 
             let core_window = core_window
                 .build()
@@ -53,7 +82,7 @@ pub fn init(app: &mut App) -> Result<(), Box<dyn std::error::Error>> {
             // Wrap the window in Arc<Mutex<_>> to manage ownership across threads
             let window = Arc::new(Mutex::new(core_window));
 
-            let main_view =
+            let mut main_view =
                 WebviewBuilder::new("main", WebviewUrl::App("https://chatgpt.com".into()))
                     .auto_resize()
                     .on_download({
@@ -90,9 +119,11 @@ pub fn init(app: &mut App) -> Result<(), Box<dyn std::error::Error>> {
                             true
                         }
                     })
-                    .initialization_script(&AppConf::load_script(&handle, "ask.js"))
-                    .initialization_script(&AppConf::load_script(&handle, "deep_research.js"))
-                    .initialization_script(INIT_SCRIPT);
+                    .initialization_script(&selector_pack.init_script());
+            for script in &user_scripts {
+                main_view = main_view.initialization_script(script);
+            }
+            let main_view = main_view.initialization_script(INIT_SCRIPT);
 
             let titlebar_view =
                 WebviewBuilder::new("titlebar", WebviewUrl::App("index.html".into())).auto_resize();
@@ -174,9 +205,22 @@ pub fn init(app: &mut App) -> Result<(), Box<dyn std::error::Error>> {
                     }
                 };
 
+            let event_handle = handle.clone();
             win.on_window_event(move |event| {
-                let conf = &AppConf::load(&handle).unwrap();
-                let ask_mode_height = if conf.ask_mode { ASK_HEIGHT } else { 0.0 };
+                // Windows following the OS theme pick up changes by themselves; the
+                // frontends are told through the broadcast.
+                if let WindowEvent::ThemeChanged(theme) = event {
+                    if AppConf::get_theme().is_none() {
+                        window::broadcast_theme(&event_handle, "system", *theme);
+                    }
+                    return;
+                }
+
+                let ask_mode_height = if ASK_MODE.load(Ordering::Relaxed) {
+                    ASK_HEIGHT
+                } else {
+                    0.0
+                };
                 let ask_height = (scale_factor * ask_mode_height).round() as u32;
 
                 if let WindowEvent::Resized(size) = event {
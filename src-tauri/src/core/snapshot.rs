@@ -0,0 +1,303 @@
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use log::{error, info};
+use rusqlite::{Connection, DatabaseName};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tauri::{AppHandle, Manager};
+
+use crate::core::{db, error::AppError, settings};
+
+static SNAPSHOT_PREFIX: &str = "aitodo-";
+static SNAPSHOT_EXT: &str = ".db";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    pub name: String,
+    pub path: String,
+    pub size: u64,
+    pub created_at: String,
+}
+
+/// Directory holding rolling snapshots, next to `aitodo.db`.
+pub fn get_snapshots_path(app: &AppHandle) -> Result<PathBuf> {
+    Ok(app.path().app_data_dir()?.join("backups"))
+}
+
+/// Copies the live database into `dir` using SQLite's online backup API.
+pub fn take_snapshot<P: AsRef<Path>>(conn: &Connection, dir: P) -> Result<SnapshotInfo> {
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir)?;
+
+    // Never overwrite an existing snapshot: bump the timestamp until the name is free, so
+    // names stay unique and sortable.
+    let mut now = Utc::now();
+    let (name, path) = loop {
+        let name = format!(
+            "{}{}{}",
+            SNAPSHOT_PREFIX,
+            now.format("%Y%m%d-%H%M%S-%3f"),
+            SNAPSHOT_EXT
+        );
+        let path = dir.join(&name);
+        if !path.exists() {
+            break (name, path);
+        }
+        now += chrono::Duration::milliseconds(1);
+    };
+
+    conn.backup(DatabaseName::Main, &path, None)
+        .with_context(|| format!("Failed to snapshot database to {}", path.display()))?;
+
+    Ok(SnapshotInfo {
+        name,
+        size: fs::metadata(&path).map(|m| m.len()).unwrap_or(0),
+        path: path.to_string_lossy().to_string(),
+        created_at: now.to_rfc3339(),
+    })
+}
+
+/// Lists snapshots in `dir`, newest first.
+pub fn list_snapshots<P: AsRef<Path>>(dir: P) -> Result<Vec<SnapshotInfo>> {
+    let dir = dir.as_ref();
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut snapshots = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !is_snapshot_name(&name) {
+            continue;
+        }
+
+        let metadata = entry.metadata()?;
+        let created_at = metadata
+            .modified()
+            .map(|t| chrono::DateTime::<Utc>::from(t).to_rfc3339())
+            .unwrap_or_default();
+
+        snapshots.push(SnapshotInfo {
+            name,
+            path: entry.path().to_string_lossy().to_string(),
+            size: metadata.len(),
+            created_at,
+        });
+    }
+
+    // Names embed a sortable timestamp.
+    snapshots.sort_by(|a, b| b.name.cmp(&a.name));
+    Ok(snapshots)
+}
+
+/// Removes all but the `keep` newest snapshots and returns the names that were deleted.
+pub fn prune_snapshots<P: AsRef<Path>>(dir: P, keep: usize) -> Result<Vec<String>> {
+    let mut removed = Vec::new();
+    for snapshot in list_snapshots(dir)?.into_iter().skip(keep) {
+        fs::remove_file(&snapshot.path)?;
+        removed.push(snapshot.name);
+    }
+    Ok(removed)
+}
+
+fn snapshot_path(dir: &Path, name: &str) -> Result<PathBuf> {
+    if !is_snapshot_name(name) {
+        bail!(AppError::Validation(format!(
+            "Invalid snapshot name: {}",
+            name
+        )));
+    }
+
+    let path = dir.join(name);
+    if !path.exists() {
+        bail!(AppError::NotFound(format!("Snapshot not found: {}", name)));
+    }
+    Ok(path)
+}
+
+/// Overwrites the live database with the snapshot `name` from `dir`, then brings its
+/// schema up to date.
+pub fn restore_snapshot<P: AsRef<Path>>(conn: &mut Connection, dir: P, name: &str) -> Result<()> {
+    let path = snapshot_path(dir.as_ref(), name)?;
+
+    // Make sure the file is a readable database before touching the live one.
+    let status: String = Connection::open(&path)?
+        .query_row("PRAGMA integrity_check", [], |row| row.get(0))
+        .with_context(|| format!("Snapshot is not a valid database: {}", name))?;
+    if status != "ok" {
+        bail!(AppError::Validation(format!(
+            "Snapshot failed integrity check: {}",
+            status
+        )));
+    }
+
+    conn.restore(
+        DatabaseName::Main,
+        &path,
+        None::<fn(rusqlite::backup::Progress)>,
+    )
+    .with_context(|| format!("Failed to restore snapshot {}", name))?;
+    // Snapshots taken by an older version lack columns the rest of the app selects.
+    db::upgrade_schema(conn).context("Failed to upgrade restored snapshot schema")?;
+    Ok(())
+}
+
+fn is_snapshot_name(name: &str) -> bool {
+    name.starts_with(SNAPSHOT_PREFIX)
+        && name.ends_with(SNAPSHOT_EXT)
+        && !name.contains(['/', '\\'])
+        && !name.contains("..")
+}
+
+/// Takes a snapshot of the app database and applies the retention policy.
+pub fn snapshot_now(app: &AppHandle) -> Result<SnapshotInfo> {
+    let conf = settings::load()?;
+    let dir = get_snapshots_path(app)?;
+
+    let snapshot = {
+        let conn = db::reader()?;
+        take_snapshot(&conn, &dir)?
+    };
+    info!("[snapshot] Created {}", snapshot.name);
+
+    for name in prune_snapshots(&dir, conf.snapshot_retention.max(1))? {
+        info!("[snapshot] Pruned {}", name);
+    }
+
+    Ok(snapshot)
+}
+
+/// Snapshots the current state of `conn` and then restores `name` into it. Nothing is
+/// pruned, so the snapshot being restored cannot be removed on the way.
+pub fn restore_with_backup<P: AsRef<Path>>(
+    conn: &mut Connection,
+    dir: P,
+    name: &str,
+) -> Result<SnapshotInfo> {
+    let dir = dir.as_ref();
+    snapshot_path(dir, name)?;
+    let backup = take_snapshot(conn, dir)?;
+    restore_snapshot(conn, dir, name)?;
+    Ok(backup)
+}
+
+/// Restores a snapshot into the live connection, snapshotting the current state first.
+pub fn restore_now(app: &AppHandle, name: &str) -> Result<()> {
+    let dir = get_snapshots_path(app)?;
+
+    let mut conn = db::writer();
+    let backup = restore_with_backup(&mut conn, &dir, name)?;
+    info!("[snapshot] Created {}", backup.name);
+    info!("[snapshot] Restored {}", name);
+    Ok(())
+}
+
+/// Snapshots on startup and then every `snapshot_interval_hours`.
+pub fn start(app: &AppHandle) {
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Err(e) = snapshot_now(&handle) {
+                error!("[snapshot] {}", e);
+            }
+
+            let hours = settings::load()
+                .map(|conf| conf.snapshot_interval_hours)
+                .unwrap_or(6)
+                .max(1);
+            tokio::time::sleep(Duration::from_secs(hours * 3600)).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("aitodo-snapshots-{}", uuid::Uuid::new_v4()))
+    }
+
+    fn value(conn: &Connection) -> i64 {
+        conn.query_row("SELECT v FROM t", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_restore_oldest_with_full_retention() {
+        let dir = temp_dir();
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE t (v INTEGER); INSERT INTO t VALUES (1);")
+            .unwrap();
+
+        let oldest = take_snapshot(&conn, &dir).unwrap();
+        for v in 2..=3 {
+            conn.execute("UPDATE t SET v = ?1", [v]).unwrap();
+            take_snapshot(&conn, &dir).unwrap();
+        }
+        // Snapshots taken within the same second do not overwrite each other.
+        assert_eq!(list_snapshots(&dir).unwrap().len(), 3);
+        assert!(prune_snapshots(&dir, 3).unwrap().is_empty());
+
+        conn.execute("UPDATE t SET v = 4", []).unwrap();
+        let backup = restore_with_backup(&mut conn, &dir, &oldest.name).unwrap();
+        assert_eq!(value(&conn), 1);
+
+        let names: Vec<String> = list_snapshots(&dir)
+            .unwrap()
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        assert_eq!(names.len(), 4);
+        assert_eq!(names[0], backup.name);
+        assert!(names.contains(&oldest.name));
+
+        // The pre-restore snapshot holds the state that was replaced.
+        restore_snapshot(&mut conn, &dir, &backup.name).unwrap();
+        assert_eq!(value(&conn), 4);
+
+        assert!(restore_with_backup(&mut conn, &dir, "aitodo-missing.db").is_err());
+        assert_eq!(list_snapshots(&dir).unwrap().len(), 4);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_restore_upgrades_old_schema() {
+        let dir = temp_dir();
+        let old = Connection::open_in_memory().unwrap();
+        old.execute_batch(
+            "CREATE TABLE todos (id TEXT PRIMARY KEY, title TEXT NOT NULL, description TEXT,
+                 url TEXT, status TEXT DEFAULT 'pending', created_at DATETIME, updated_at DATETIME);
+             CREATE TABLE research_results (id TEXT PRIMARY KEY, todo_id TEXT NOT NULL,
+                 source TEXT, content TEXT, started_at DATETIME, completed_at DATETIME,
+                 duration_seconds INTEGER);
+             INSERT INTO todos (id, title) VALUES ('t1', 'Old');",
+        )
+        .unwrap();
+        let snapshot = take_snapshot(&old, &dir).unwrap();
+
+        let mut conn = Connection::open_in_memory().unwrap();
+        db::upgrade_schema(&conn).unwrap();
+        restore_snapshot(&mut conn, &dir, &snapshot.name).unwrap();
+
+        let (title, tags, version): (String, String, Option<i64>) = conn
+            .query_row(
+                "SELECT t.title, t.tags, (SELECT MAX(converter_version) FROM research_results)
+                 FROM todos t WHERE t.deleted_at IS NULL AND t.next_run_at IS NULL",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(title, "Old");
+        assert_eq!(tags, "[]");
+        assert_eq!(version, None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,32 +1,45 @@
 use anyhow::{Context, Result};
-use log::{error, info};
+use log::{error, info, warn};
 use regex::Regex;
 use semver::Version;
 use serde_json::json;
 use std::{
     fs::{self, File},
     io::{Read, Write},
-    path::Path,
+    path::{Path, PathBuf},
 };
 
 pub static SCRIPT_ASK: &[u8] = include_bytes!("../../scripts/ask.js");
+pub static SCRIPT_DEEP_RESEARCH: &[u8] = include_bytes!("../../scripts/deep_research.js");
 
-/// Struct representing the template with the script data.
+/// Every script bundled with the app, by file name. Each carries an `@version` header and
+/// is rewritten in the scripts directory whenever the bundled version is newer.
+pub static SCRIPTS: [(&str, &[u8]); 2] = [
+    ("ask.js", SCRIPT_ASK),
+    ("deep_research.js", SCRIPT_DEEP_RESEARCH),
+];
+
+/// Subdirectory of the scripts directory for user overrides. A script placed there is
+/// injected instead of the managed copy of the same name and is never written by the app.
+pub static OVERRIDES_DIR: &str = "overrides";
+
+/// The scripts directory, with every bundled script brought up to date.
 #[derive(Debug)]
 pub struct Template {
-    pub ask: Vec<u8>,
+    pub dir: PathBuf,
 }
 
 impl Template {
-    /// Creates a new Template instance, initializing it with the script data.
+    /// Writes missing or outdated bundled scripts to `template_dir`, leaving user overrides
+    /// untouched.
     pub fn new<P: AsRef<Path>>(template_dir: P) -> Self {
-        let template_dir = template_dir.as_ref();
-        let mut template = Template::default();
-
-        let files = vec![(template_dir.join("ask.js"), &mut template.ask)];
+        let template = Template {
+            dir: template_dir.as_ref().to_path_buf(),
+        };
 
-        for (filename, _) in files {
-            match update_or_create_file(&filename, SCRIPT_ASK) {
+        for (name, data) in SCRIPTS {
+            let filename = template.dir.join(name);
+            match update_or_create_file(&filename, data) {
                 Ok(updated) => {
                     if updated {
                         info!("Script updated or created: {}", filename.display());
@@ -38,22 +51,53 @@ impl Template {
                     error!("Failed to process script, {}: {}", filename.display(), e);
                 }
             }
+
+            let override_file = template.override_path(name);
+            if let Ok(contents) = read_file_contents(&override_file) {
+                if is_outdated(&contents, data) {
+                    warn!(
+                        "Override {} is older than the bundled script, which may have fixes it lacks",
+                        override_file.display()
+                    );
+                }
+            }
+        }
+
+        if let Err(e) = fs::create_dir_all(template.dir.join(OVERRIDES_DIR)) {
+            error!("Failed to create the overrides directory: {}", e);
         }
 
         template
     }
-}
 
-impl Default for Template {
-    fn default() -> Template {
-        Template {
-            ask: Vec::from(SCRIPT_ASK),
+    pub fn override_path(&self, name: &str) -> PathBuf {
+        self.dir.join(OVERRIDES_DIR).join(name)
+    }
+
+    /// The script to inject: the user override if there is one, else the managed copy,
+    /// else the script bundled with the app.
+    pub fn load(&self, name: &str) -> String {
+        for filename in [self.override_path(name), self.dir.join(name)] {
+            if let Ok(contents) = fs::read_to_string(&filename) {
+                return contents;
+            }
+        }
+
+        match SCRIPTS.iter().find(|(script, _)| *script == name) {
+            Some((_, data)) => {
+                warn!("Script {} is missing on disk, using the bundled copy", name);
+                String::from_utf8_lossy(data).into_owned()
+            }
+            None => {
+                error!("Unknown script: {}", name);
+                String::new()
+            }
         }
     }
 }
 
 /// Reads the version information from the given data.
-fn read_version_info(data: &[u8]) -> Result<serde_json::Value> {
+pub fn read_version_info(data: &[u8]) -> Result<serde_json::Value> {
     let content = String::from_utf8_lossy(data);
     let re_name = Regex::new(r"@name\s+(.*?)\n").context("Failed to compile name regex")?;
     let re_version =
@@ -84,6 +128,32 @@ fn read_version_info(data: &[u8]) -> Result<serde_json::Value> {
     Ok(json_data)
 }
 
+/// Reads the URL patterns of the `@match` headers, in order.
+pub fn read_matches(data: &[u8]) -> Vec<String> {
+    let content = String::from_utf8_lossy(data);
+    let re_match = Regex::new(r"@match\s+(.*?)\n").expect("valid match regex");
+    re_match
+        .captures_iter(&content)
+        .filter_map(|cap| cap.get(1))
+        .map(|m| m.as_str().trim().to_string())
+        .filter(|m| !m.is_empty())
+        .collect()
+}
+
+fn version_of(data: &[u8]) -> Option<Version> {
+    let info = read_version_info(data).ok()?;
+    Version::parse(info.get("version")?.as_str()?).ok()
+}
+
+/// Whether `bundled` carries a newer version than `current`. Unversioned scripts are
+/// never reported as outdated.
+fn is_outdated(current: &[u8], bundled: &[u8]) -> bool {
+    match (version_of(current), version_of(bundled)) {
+        (Some(current), Some(bundled)) => bundled > current,
+        _ => false,
+    }
+}
+
 /// Reads the contents of the given file.
 fn read_file_contents<P: AsRef<Path>>(filename: P) -> Result<Vec<u8>> {
     let filename = filename.as_ref();
@@ -137,9 +207,9 @@ fn update_or_create_file<P: AsRef<Path>>(filename: P, new_data: &[u8]) -> Result
                         .and_then(|v| v.as_str())
                         .unwrap_or("");
 
-                    if current_version.is_empty()
-                        || Version::parse(new_version)? > Version::parse(current_version)?
-                    {
+                    // A missing or unparsable version counts as outdated.
+                    let new_version = Version::parse(new_version)?;
+                    if Version::parse(current_version).map_or(true, |v| new_version > v) {
                         write_file_contents(filename, new_data)?;
                         info!("{} → {}", current_version, new_version);
                         Ok(true)
@@ -165,3 +235,78 @@ fn update_or_create_file<P: AsRef<Path>>(filename: P, new_data: &[u8]) -> Result
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn script(version: &str, body: &str) -> Vec<u8> {
+        format!(
+            "/**\n * @name test.js\n * @version {}\n */\n{}\n",
+            version, body
+        )
+        .into_bytes()
+    }
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("aitodo-scripts-{}", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn test_update_or_create_file_follows_versions() {
+        let dir = temp_dir();
+        let filename = dir.join("test.js");
+
+        assert!(update_or_create_file(&filename, &script("0.1.0", "first")).unwrap());
+        assert!(!update_or_create_file(&filename, &script("0.1.0", "same")).unwrap());
+        assert!(update_or_create_file(&filename, &script("0.2.0", "upgrade")).unwrap());
+        assert!(!update_or_create_file(&filename, &script("0.1.5", "older")).unwrap());
+        assert_eq!(
+            read_file_contents(&filename).unwrap(),
+            script("0.2.0", "upgrade")
+        );
+
+        // Copies with a broken or missing header are replaced.
+        fs::write(&filename, script("latest", "broken")).unwrap();
+        assert!(update_or_create_file(&filename, &script("0.2.0", "fixed")).unwrap());
+        fs::write(&filename, "// no header\n").unwrap();
+        assert!(update_or_create_file(&filename, &script("0.2.0", "fixed")).unwrap());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_bundled_scripts_are_managed_and_overridable() {
+        let dir = temp_dir();
+        let template = Template::new(&dir);
+        for (name, data) in SCRIPTS {
+            assert!(version_of(data).is_some(), "{} has no @version", name);
+            assert_eq!(template.load(name).as_bytes(), data);
+        }
+
+        let custom = "// my own ask.js\n";
+        fs::write(template.override_path("ask.js"), custom).unwrap();
+        fs::remove_file(dir.join("deep_research.js")).unwrap();
+        let template = Template::new(&dir);
+        assert_eq!(template.load("ask.js"), custom);
+        assert!(dir.join("deep_research.js").exists());
+
+        // The override survives an upgrade of the managed copy.
+        fs::write(dir.join("ask.js"), script("0.0.1", "old")).unwrap();
+        let template = Template::new(&dir);
+        assert_eq!(read_file_contents(dir.join("ask.js")).unwrap(), SCRIPT_ASK);
+        assert_eq!(template.load("ask.js"), custom);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_falls_back_to_bundled() {
+        let template = Template { dir: temp_dir() };
+        assert_eq!(
+            template.load("deep_research.js").as_bytes(),
+            SCRIPT_DEEP_RESEARCH
+        );
+        assert_eq!(template.load("missing.js"), "");
+    }
+}
@@ -1,10 +1,17 @@
-use chrono::{DateTime, Utc};
-use rusqlite::{params, Result as SqliteResult};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use rusqlite::{Connection, Result as SqliteResult};
 use serde::{Deserialize, Serialize};
-use uuid::Uuid;
 
-use crate::core::db::get_db;
 pub use crate::core::research::ResearchResult;
+use crate::core::{
+    db,
+    recurrence::Recurrence,
+    repository::{SqliteTodoRepository, TodoRepository},
+};
+
+/// Column list matching the field order read by [`row_to_todo`].
+pub const TODO_COLUMNS: &str =
+    "id, title, description, url, status, created_at, updated_at, tags, due_date, deleted_at, recurrence, next_run_at";
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -28,13 +35,18 @@ impl TodoStatus {
     }
 
     pub fn from_str(s: &str) -> Self {
+        Self::parse(s).unwrap_or(TodoStatus::Pending)
+    }
+
+    /// Like `from_str`, but returns `None` for unknown values instead of defaulting.
+    pub fn parse(s: &str) -> Option<Self> {
         match s {
-            "pending" => TodoStatus::Pending,
-            "researching" => TodoStatus::Researching,
-            "review" => TodoStatus::Review,
-            "done" => TodoStatus::Done,
-            "archived" => TodoStatus::Archived,
-            _ => TodoStatus::Pending,
+            "pending" => Some(TodoStatus::Pending),
+            "researching" => Some(TodoStatus::Researching),
+            "review" => Some(TodoStatus::Review),
+            "done" => Some(TodoStatus::Done),
+            "archived" => Some(TodoStatus::Archived),
+            _ => None,
         }
     }
 }
@@ -48,21 +60,39 @@ pub struct Todo {
     pub status: TodoStatus,
     pub created_at: String,
     pub updated_at: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub due_date: Option<String>,
+    /// Set while the todo sits in the trash.
+    #[serde(default)]
+    pub deleted_at: Option<String>,
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>,
+    /// When the recurrence next re-runs research (RFC 3339).
+    #[serde(default)]
+    pub next_run_at: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct CreateTodoInput {
     pub title: String,
     pub description: Option<String>,
     pub url: Option<String>,
+    /// Initial status; new todos start as pending when omitted.
+    pub status: Option<TodoStatus>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub due_date: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct UpdateTodoInput {
     pub title: Option<String>,
     pub description: Option<String>,
     pub url: Option<String>,
     pub status: Option<TodoStatus>,
+    pub tags: Option<Vec<String>>,
+    pub due_date: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -71,7 +101,7 @@ pub struct TodoWithResearch {
     pub research: Option<ResearchResult>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct StatusCounts {
     pub pending: i64,
     pub researching: i64,
@@ -81,160 +111,104 @@ pub struct StatusCounts {
     pub total: i64,
 }
 
-pub fn create_todo(input: CreateTodoInput) -> SqliteResult<Todo> {
-    let conn = get_db().lock().unwrap();
-    let id = Uuid::new_v4().to_string();
-    let now = Utc::now().to_rfc3339();
+impl StatusCounts {
+    /// Adds `count` todos with the given status.
+    pub fn add(&mut self, status: &str, count: i64) {
+        match status {
+            "pending" => self.pending += count,
+            "researching" => self.researching += count,
+            "review" => self.review += count,
+            "done" => self.done += count,
+            "archived" => self.archived += count,
+            _ => {}
+        }
+        self.total += count;
+    }
+}
 
-    conn.execute(
-        "INSERT INTO todos (id, title, description, url, status, created_at, updated_at) 
-         VALUES (?1, ?2, ?3, ?4, 'pending', ?5, ?5)",
-        params![id, input.title, input.description, input.url, now],
-    )?;
+pub fn create_todo(input: CreateTodoInput) -> SqliteResult<Todo> {
+    insert_todo(&db::writer(), input)
+}
 
-    Ok(Todo {
-        id,
-        title: input.title,
-        description: input.description,
-        url: input.url,
-        status: TodoStatus::Pending,
-        created_at: now.clone(),
-        updated_at: now,
-    })
+/// Inserts a todo on the given connection, so callers can batch inserts in a transaction.
+pub fn insert_todo(conn: &Connection, input: CreateTodoInput) -> SqliteResult<Todo> {
+    SqliteTodoRepository::new(conn).create(input)
 }
 
 pub fn get_todos(status_filter: Option<&str>) -> SqliteResult<Vec<Todo>> {
-    let conn = get_db().lock().unwrap();
-
-    let sql = match status_filter {
-        Some(_) => {
-            "SELECT id, title, description, url, status, created_at, updated_at 
-                    FROM todos WHERE status = ?1 ORDER BY created_at DESC"
-        }
-        None => {
-            "SELECT id, title, description, url, status, created_at, updated_at 
-                 FROM todos WHERE status != 'archived' ORDER BY created_at DESC"
-        }
-    };
-
-    let mut stmt = conn.prepare(sql)?;
-
-    let rows = if let Some(status) = status_filter {
-        stmt.query_map([status], row_to_todo)?
-    } else {
-        stmt.query_map([], row_to_todo)?
-    };
-
-    rows.collect()
+    let conn = db::reader()?;
+    SqliteTodoRepository::new(&conn).list(status_filter)
 }
 
 pub fn get_todo_by_id(id: &str) -> SqliteResult<Option<Todo>> {
-    let conn = get_db().lock().unwrap();
-    let mut stmt = conn.prepare(
-        "SELECT id, title, description, url, status, created_at, updated_at 
-         FROM todos WHERE id = ?1",
-    )?;
-
-    let mut rows = stmt.query([id])?;
-    if let Some(row) = rows.next()? {
-        Ok(Some(row_to_todo(row)?))
-    } else {
-        Ok(None)
-    }
+    let conn = db::reader()?;
+    SqliteTodoRepository::new(&conn).get(id)
 }
 
 pub fn get_todo_with_research(id: &str) -> SqliteResult<Option<TodoWithResearch>> {
-    let todo = get_todo_by_id(id)?;
+    let conn = db::reader()?;
+    let repo = SqliteTodoRepository::new(&conn);
 
-    if let Some(todo) = todo {
-        let research = get_research_by_todo_id(&todo.id)?;
-        Ok(Some(TodoWithResearch { todo, research }))
-    } else {
-        Ok(None)
+    match repo.get(id)? {
+        Some(todo) => {
+            let research = repo.latest_research(&todo.id)?;
+            Ok(Some(TodoWithResearch { todo, research }))
+        }
+        None => Ok(None),
     }
 }
 
 pub fn update_todo(id: &str, input: UpdateTodoInput) -> SqliteResult<Option<Todo>> {
-    let conn = get_db().lock().unwrap();
-    let now = Utc::now().to_rfc3339();
-
-    let current = {
-        let mut stmt = conn.prepare(
-            "SELECT id, title, description, url, status, created_at, updated_at FROM todos WHERE id = ?1",
-        )?;
-        let mut rows = stmt.query([id])?;
-        match rows.next()? {
-            Some(row) => row_to_todo(row)?,
-            None => return Ok(None),
-        }
-    };
-
-    let new_title = input.title.unwrap_or(current.title);
-    let new_description = input.description.or(current.description);
-    let new_url = input.url.or(current.url);
-    let new_status = input.status.unwrap_or(current.status);
-
-    conn.execute(
-        "UPDATE todos SET title = ?1, description = ?2, url = ?3, status = ?4, updated_at = ?5 
-         WHERE id = ?6",
-        params![
-            new_title,
-            new_description,
-            new_url,
-            new_status.as_str(),
-            now,
-            id
-        ],
-    )?;
-
-    Ok(Some(Todo {
-        id: id.to_string(),
-        title: new_title,
-        description: new_description,
-        url: new_url,
-        status: new_status,
-        created_at: current.created_at,
-        updated_at: now,
-    }))
+    SqliteTodoRepository::new(&db::writer()).update(id, input)
 }
 
+/// Moves a todo to the trash; its research results are kept until it is purged.
 pub fn delete_todo(id: &str) -> SqliteResult<bool> {
-    let conn = get_db().lock().unwrap();
-    let affected = conn.execute("DELETE FROM todos WHERE id = ?1", [id])?;
-    Ok(affected > 0)
+    SqliteTodoRepository::new(&db::writer()).delete(id)
 }
 
-pub fn get_status_counts() -> SqliteResult<StatusCounts> {
-    let conn = get_db().lock().unwrap();
-    let mut stmt = conn.prepare("SELECT status, COUNT(*) as count FROM todos GROUP BY status")?;
-
-    let mut counts = StatusCounts {
-        pending: 0,
-        researching: 0,
-        review: 0,
-        done: 0,
-        archived: 0,
-        total: 0,
-    };
-
-    let rows = stmt.query_map([], |row| {
-        Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
-    })?;
-
-    for row in rows {
-        let (status, count) = row?;
-        match status.as_str() {
-            "pending" => counts.pending = count,
-            "researching" => counts.researching = count,
-            "review" => counts.review = count,
-            "done" => counts.done = count,
-            "archived" => counts.archived = count,
-            _ => {}
-        }
-        counts.total += count;
-    }
+/// Every research run of a todo, newest first; re-runs never replace earlier results.
+pub fn get_research_history(todo_id: &str) -> SqliteResult<Vec<ResearchResult>> {
+    let conn = db::reader()?;
+    SqliteTodoRepository::new(&conn).list_research(todo_id)
+}
+
+pub fn set_recurrence(
+    id: &str,
+    recurrence: Option<Recurrence>,
+    next_run_at: Option<String>,
+) -> SqliteResult<Option<Todo>> {
+    SqliteTodoRepository::new(&db::writer()).set_recurrence(id, recurrence, next_run_at)
+}
+
+pub fn get_due_recurring(now: DateTime<Utc>) -> SqliteResult<Vec<Todo>> {
+    let conn = db::reader()?;
+    SqliteTodoRepository::new(&conn).due_recurring(&now.to_rfc3339())
+}
+
+pub fn get_trash() -> SqliteResult<Vec<Todo>> {
+    let conn = db::reader()?;
+    SqliteTodoRepository::new(&conn).list_trash()
+}
 
-    Ok(counts)
+pub fn restore_todo(id: &str) -> SqliteResult<Option<Todo>> {
+    SqliteTodoRepository::new(&db::writer()).restore(id)
+}
+
+/// Permanently deletes a trashed todo together with its research results.
+pub fn purge_todo(id: &str) -> SqliteResult<bool> {
+    SqliteTodoRepository::new(&db::writer()).purge(id)
+}
+
+/// Permanently deletes todos trashed more than `days` days ago; `0` empties the trash.
+pub fn purge_trash(days: u64) -> SqliteResult<usize> {
+    let cutoff = Utc::now() - chrono::Duration::days(days as i64);
+    SqliteTodoRepository::new(&db::writer()).purge_deleted_before(&cutoff.to_rfc3339())
+}
+
+pub fn get_status_counts() -> SqliteResult<StatusCounts> {
+    let conn = db::reader()?;
+    SqliteTodoRepository::new(&conn).status_counts()
 }
 
 pub fn save_research_result(
@@ -242,68 +216,20 @@ pub fn save_research_result(
     source: &str,
     content: &str,
     raw_html: Option<&str>,
+    converter_version: Option<i64>,
     started_at: &str,
 ) -> SqliteResult<ResearchResult> {
-    let mut conn = get_db().lock().unwrap();
-    let id = Uuid::new_v4().to_string();
-    let completed_at = Utc::now().to_rfc3339();
-
-    let started: DateTime<Utc> = started_at.parse().unwrap_or_else(|_| Utc::now());
-    let completed: DateTime<Utc> = completed_at.parse().unwrap_or_else(|_| Utc::now());
-    let duration = (completed - started).num_seconds();
-
-    let tx = conn.transaction()?;
-
-    tx.execute(
-        "INSERT INTO research_results (id, todo_id, source, content, raw_html, started_at, completed_at, duration_seconds)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-        params![id, todo_id, source, content, raw_html, started_at, completed_at, duration],
-    )?;
-
-    tx.execute(
-        "UPDATE todos SET status = 'review', updated_at = ?1 WHERE id = ?2",
-        params![completed_at, todo_id],
-    )?;
-
-    tx.commit()?;
-
-    Ok(ResearchResult {
-        id,
-        todo_id: todo_id.to_string(),
-        source: source.to_string(),
-        content: Some(content.to_string()),
-        raw_html: raw_html.map(|s| s.to_string()),
-        started_at: Some(started_at.to_string()),
-        completed_at: Some(completed_at),
-        duration_seconds: Some(duration),
-    })
-}
-
-pub fn get_research_by_todo_id(todo_id: &str) -> SqliteResult<Option<ResearchResult>> {
-    let conn = get_db().lock().unwrap();
-    let mut stmt = conn.prepare(
-        "SELECT id, todo_id, source, content, raw_html, started_at, completed_at, duration_seconds
-         FROM research_results WHERE todo_id = ?1 ORDER BY completed_at DESC LIMIT 1",
-    )?;
-
-    let mut rows = stmt.query([todo_id])?;
-    if let Some(row) = rows.next()? {
-        Ok(Some(ResearchResult {
-            id: row.get(0)?,
-            todo_id: row.get(1)?,
-            source: row.get(2)?,
-            content: row.get(3)?,
-            raw_html: row.get(4)?,
-            started_at: row.get(5)?,
-            completed_at: row.get(6)?,
-            duration_seconds: row.get(7)?,
-        }))
-    } else {
-        Ok(None)
-    }
+    SqliteTodoRepository::new(&db::writer()).save_research(
+        todo_id,
+        source,
+        content,
+        raw_html,
+        converter_version,
+        started_at,
+    )
 }
 
-fn row_to_todo(row: &rusqlite::Row) -> SqliteResult<Todo> {
+pub fn row_to_todo(row: &rusqlite::Row) -> SqliteResult<Todo> {
     Ok(Todo {
         id: row.get(0)?,
         title: row.get(1)?,
@@ -312,5 +238,42 @@ fn row_to_todo(row: &rusqlite::Row) -> SqliteResult<Todo> {
         status: TodoStatus::from_str(&row.get::<_, String>(4)?),
         created_at: row.get(5)?,
         updated_at: row.get(6)?,
+        tags: tags_from_json(row.get::<_, Option<String>>(7)?.as_deref()),
+        due_date: row.get(8)?,
+        deleted_at: row.get(9)?,
+        recurrence: row
+            .get::<_, Option<String>>(10)?
+            .and_then(|v| serde_json::from_str(&v).ok()),
+        next_run_at: row.get(11)?,
     })
 }
+
+/// Normalizes a due date to `YYYY-MM-DD`, or RFC 3339 when it carries a time of day.
+pub fn parse_due_date(value: &str) -> Option<String> {
+    let value = value.trim();
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Some(date.format("%Y-%m-%d").to_string());
+    }
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Some(datetime.with_timezone(&Utc).to_rfc3339());
+    }
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M")
+        .ok()
+        .map(|datetime| datetime.and_utc().to_rfc3339())
+}
+
+/// Recurrence rules are stored as JSON; `None` clears the column.
+pub fn recurrence_to_json(recurrence: Option<&Recurrence>) -> Option<String> {
+    recurrence.and_then(|r| serde_json::to_string(r).ok())
+}
+
+/// Tags are stored as a JSON array in a single text column.
+pub fn tags_to_json(tags: &[String]) -> String {
+    serde_json::to_string(tags).unwrap_or_else(|_| "[]".to_string())
+}
+
+pub fn tags_from_json(value: Option<&str>) -> Vec<String> {
+    value
+        .and_then(|v| serde_json::from_str(v).ok())
+        .unwrap_or_default()
+}
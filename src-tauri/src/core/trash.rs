@@ -0,0 +1,35 @@
+use anyhow::Result;
+use log::{error, info};
+use std::time::Duration;
+
+use crate::core::{settings, todo};
+
+/// How often the trash is checked for expired todos.
+const PURGE_INTERVAL: Duration = Duration::from_secs(24 * 3600);
+
+/// Permanently deletes todos that have been in the trash longer than
+/// `trash_retention_days`. A retention of `0` keeps trashed todos forever.
+pub fn purge_expired() -> Result<usize> {
+    let conf = settings::load()?;
+    if conf.trash_retention_days == 0 {
+        return Ok(0);
+    }
+
+    let purged = todo::purge_trash(conf.trash_retention_days)?;
+    if purged > 0 {
+        info!("[trash] Purged {} todo(s) from the trash", purged);
+    }
+    Ok(purged)
+}
+
+/// Purges on startup and then once a day.
+pub fn start() {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Err(e) = purge_expired() {
+                error!("[trash] {}", e);
+            }
+            tokio::time::sleep(PURGE_INTERVAL).await;
+        }
+    });
+}
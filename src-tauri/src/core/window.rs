@@ -1,6 +1,46 @@
-use tauri::{command, AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+use log::error;
+use serde::Serialize;
+use tauri::{command, AppHandle, Emitter, Manager, Theme, WebviewUrl, WebviewWindowBuilder};
 
-use crate::core::constant::{WINDOW_SETTINGS, WINDOW_TODO};
+use crate::core::{
+    conf::AppConf,
+    constant::{WINDOW_SETTINGS, WINDOW_TODO},
+};
+
+/// Event broadcast to every window when the theme in use changes.
+pub static THEME_CHANGED: &str = "theme_changed";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ThemeChanged {
+    /// The configured mode: `light`, `dark` or `system`.
+    pub mode: String,
+    /// The theme in use, `light` or `dark`.
+    pub theme: &'static str,
+}
+
+pub fn broadcast_theme(app: &AppHandle, mode: &str, theme: Theme) {
+    let payload = ThemeChanged {
+        mode: mode.to_string(),
+        theme: match theme {
+            Theme::Dark => "dark",
+            _ => "light",
+        },
+    };
+    if let Err(e) = app.emit(THEME_CHANGED, payload) {
+        error!("[window::broadcast_theme] {}", e);
+    }
+}
+
+/// Applies the configured theme to every open window without restarting, so a running
+/// research session in the main webview survives.
+pub fn apply_theme(app: &AppHandle, mode: &str) {
+    let theme = AppConf::get_theme();
+    app.set_theme(theme);
+
+    // In system mode the windows report the OS theme.
+    let current = theme.or_else(|| app.get_window("core").and_then(|win| win.theme().ok()));
+    broadcast_theme(app, mode, current.unwrap_or(Theme::Light));
+}
 
 #[command]
 pub fn open_settings(app: AppHandle) {
@@ -10,6 +50,7 @@ pub fn open_settings(app: AppHandle) {
         }
         None => {
             WebviewWindowBuilder::new(&app, WINDOW_SETTINGS, WebviewUrl::App("index.html".into()))
+                .theme(AppConf::get_theme())
                 .build()
                 .unwrap();
         }
@@ -24,10 +65,11 @@ pub fn open_todo_app(app: AppHandle) {
             window.set_focus().unwrap();
         }
         None => {
-            WebviewWindowBuilder::new(&app, "todo", WebviewUrl::App("index.html".into()))
+            WebviewWindowBuilder::new(&app, WINDOW_TODO, WebviewUrl::App("index.html".into()))
                 .title("AI Todo")
                 .inner_size(1000.0, 700.0)
                 .min_inner_size(600.0, 400.0)
+                .theme(AppConf::get_theme())
                 .build()
                 .unwrap();
         }
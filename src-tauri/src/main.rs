@@ -2,7 +2,10 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod core;
-use core::{cmd, db, setup, window};
+use core::{
+    cmd, db, legacy, markdown, notify, recurrence, reminders, research, rules, settings, setup,
+    snapshot, trash, window,
+};
 use tauri::{
     menu::{Menu, MenuItem, Submenu},
     Manager,
@@ -22,6 +25,7 @@ fn main() {
             cmd::view_go_back,
             cmd::set_view_ask,
             cmd::get_app_conf,
+            cmd::cmd_update_settings,
             cmd::window_pin,
             cmd::ask_sync,
             cmd::ask_send,
@@ -34,12 +38,53 @@ fn main() {
             cmd::cmd_get_status_counts,
             cmd::cmd_start_research,
             cmd::cmd_cancel_research,
-            cmd::cmd_save_research_result,
+            cmd::cmd_export_research_pdf,
+            cmd::cmd_backup,
+            cmd::cmd_restore,
+            cmd::cmd_import_preview,
+            cmd::cmd_import_todos,
+            cmd::cmd_export_todos,
+            cmd::cmd_list_snapshots,
+            cmd::cmd_create_snapshot,
+            cmd::cmd_restore_snapshot,
+            cmd::cmd_get_trash,
+            cmd::cmd_restore_todo,
+            cmd::cmd_purge_todo,
+            cmd::cmd_empty_trash,
+            cmd::cmd_bulk_update,
+            cmd::cmd_get_status_rules,
+            cmd::cmd_set_status_rules,
+            cmd::cmd_run_status_rules,
+            cmd::cmd_set_recurrence,
+            cmd::cmd_get_research_history,
+            cmd::cmd_rerender_research,
+            cmd::cmd_get_reminders,
+            cmd::cmd_set_reminder_offsets,
+            cmd::cmd_snooze_reminder,
+            cmd::cmd_set_notification_prefs,
+            cmd::cmd_get_scripts,
+            cmd::cmd_set_script_enabled,
+            cmd::cmd_set_script_matches,
+            cmd::cmd_add_script,
+            cmd::cmd_remove_script,
+            cmd::cmd_get_selector_pack,
+            cmd::cmd_load_selector_pack,
+            cmd::cmd_automation_selftest,
             window::open_settings,
             window::open_todo_app,
         ])
         .setup(|app| {
+            legacy::migrate(app.handle())?;
             db::init_db(app.handle())?;
+            settings::migrate(app.handle())?;
+            markdown::start();
+            notify::start(app.handle());
+            research::listen(app.handle());
+            snapshot::start(app.handle());
+            trash::start();
+            rules::start();
+            recurrence::start(app.handle());
+            reminders::start(app.handle());
 
             let handle = app.handle();
             let todo_item =
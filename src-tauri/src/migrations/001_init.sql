@@ -5,7 +5,12 @@ CREATE TABLE IF NOT EXISTS todos (
     url TEXT,
     status TEXT DEFAULT 'pending' CHECK(status IN ('pending','researching','review','done','archived')),
     created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    tags TEXT DEFAULT '[]',
+    due_date TEXT,
+    deleted_at TEXT,
+    recurrence TEXT,
+    next_run_at TEXT
 );
 
 CREATE TABLE IF NOT EXISTS research_results (
@@ -16,5 +21,31 @@ CREATE TABLE IF NOT EXISTS research_results (
     raw_html TEXT,
     started_at DATETIME,
     completed_at DATETIME,
-    duration_seconds INTEGER
+    duration_seconds INTEGER,
+    converter_version INTEGER
+);
+
+CREATE TABLE IF NOT EXISTS settings (
+    key TEXT PRIMARY KEY,
+    value TEXT
+);
+
+CREATE TABLE IF NOT EXISTS reminders (
+    todo_id TEXT PRIMARY KEY REFERENCES todos(id) ON DELETE CASCADE,
+    offsets TEXT,
+    snoozed_until TEXT
+);
+
+CREATE TABLE IF NOT EXISTS reminder_log (
+    todo_id TEXT NOT NULL REFERENCES todos(id) ON DELETE CASCADE,
+    due_date TEXT NOT NULL,
+    offset_minutes INTEGER NOT NULL,
+    notified_at TEXT NOT NULL,
+    PRIMARY KEY (todo_id, due_date, offset_minutes)
+);
+
+CREATE TABLE IF NOT EXISTS user_scripts (
+    file TEXT PRIMARY KEY,
+    enabled INTEGER NOT NULL DEFAULT 1,
+    matches TEXT
 );
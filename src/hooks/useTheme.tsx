@@ -1,4 +1,5 @@
 import { useState, useEffect } from 'react';
+import { listen } from '@tauri-apps/api/event';
 import { getCurrentWindow } from '@tauri-apps/api/window';
 
 export default function useTheme() {
@@ -6,18 +7,23 @@ export default function useTheme() {
 
   useEffect(() => {
     let unlisten: Function;
+    let unlistenBroadcast: Function;
     (async () => {
       let win = getCurrentWindow();
       setTheme(await win.theme() || '');
       unlisten = await win.onThemeChanged(({ payload: newTheme }) => {
         setTheme(newTheme);
       });
+      unlistenBroadcast = await listen<I.ThemeChanged>('theme_changed', ({ payload }) => {
+        setTheme(payload.theme);
+      });
     })()
 
     return () => {
       unlisten?.();
+      unlistenBroadcast?.();
     };
   }, [])
 
   return theme;
-}
\ No newline at end of file
+}
@@ -7,6 +7,13 @@ type InvokeFn = <T>(cmd: string, args?: Record<string, unknown>) => Promise<T>;
 
 const invoke: InvokeFn = isTauri ? tauriInvoke : createMockInvoke();
 
+function errorMessage(error: unknown): string {
+  if (error && typeof error === 'object' && 'message' in error) {
+    return String((error as I.AppError).message);
+  }
+  return String(error);
+}
+
 function createMockInvoke() {
   const mockTodos: I.Todo[] = [];
   let todoIdCounter = 1;
@@ -41,9 +48,14 @@ function createMockInvoke() {
           title: input.title,
           description: input.description || null,
           url: input.url || null,
-          status: 'pending',
+          status: input.status || 'pending',
           created_at: new Date().toISOString(),
           updated_at: new Date().toISOString(),
+          tags: input.tags || [],
+          due_date: input.due_date || null,
+          deleted_at: null,
+          recurrence: null,
+          next_run_at: null,
         };
         mockTodos.push(todo);
         return todo as T;
@@ -144,7 +156,7 @@ export const useTodoStore = create<TodoState>((set, get) => ({
       const todos = await invoke<I.Todo[]>('cmd_get_todos', { status: statusParam });
       set({ todos, isLoading: false });
     } catch (error) {
-      set({ error: String(error), isLoading: false });
+      set({ error: errorMessage(error), isLoading: false });
     }
   },
 
@@ -154,7 +166,7 @@ export const useTodoStore = create<TodoState>((set, get) => ({
       const detail = await invoke<I.TodoWithResearch | null>('cmd_get_todo_detail', { id });
       set({ selectedTodo: detail, isLoading: false, detailPanelOpen: !!detail });
     } catch (error) {
-      set({ error: String(error), isLoading: false });
+      set({ error: errorMessage(error), isLoading: false });
     }
   },
 
@@ -177,7 +189,7 @@ export const useTodoStore = create<TodoState>((set, get) => ({
       set({ isLoading: false });
       return todo;
     } catch (error) {
-      set({ error: String(error), isLoading: false });
+      set({ error: errorMessage(error), isLoading: false });
       return null;
     }
   },
@@ -195,7 +207,7 @@ export const useTodoStore = create<TodoState>((set, get) => ({
       }
       set({ isLoading: false });
     } catch (error) {
-      set({ error: String(error), isLoading: false });
+      set({ error: errorMessage(error), isLoading: false });
     }
   },
 
@@ -211,7 +223,7 @@ export const useTodoStore = create<TodoState>((set, get) => ({
       }
       set({ isLoading: false });
     } catch (error) {
-      set({ error: String(error), isLoading: false });
+      set({ error: errorMessage(error), isLoading: false });
     }
   },
 
@@ -225,7 +237,7 @@ export const useTodoStore = create<TodoState>((set, get) => ({
       await fetchTodoDetail(id);
       set({ isLoading: false });
     } catch (error) {
-      set({ error: String(error), isLoading: false });
+      set({ error: errorMessage(error), isLoading: false });
     }
   },
 
@@ -239,7 +251,7 @@ export const useTodoStore = create<TodoState>((set, get) => ({
       await fetchTodoDetail(id);
       set({ isLoading: false });
     } catch (error) {
-      set({ error: String(error), isLoading: false });
+      set({ error: errorMessage(error), isLoading: false });
     }
   },
 
@@ -1,9 +1,14 @@
 declare namespace I {
   export type AppConf = {
+    version: number;
     theme: 'light' | 'dark' | 'system';
     stay_on_top: boolean;
     ask_mode: boolean;
     mac_titlebar_hidden: boolean;
+    snapshot_interval_hours: number;
+    snapshot_retention: number;
+    trash_retention_days: number;
+    notifications: NotificationPrefs;
   }
 
   export interface SVG extends React.SVGProps<SVGSVGElement> {
@@ -24,12 +29,27 @@ declare namespace I {
     status: TodoStatus;
     created_at: string;
     updated_at: string;
+    tags: string[];
+    due_date: string | null;
+    deleted_at: string | null;
+    recurrence: Recurrence | null;
+    next_run_at: string | null;
   }
 
+  // `anchor` is the local time the interval is counted from; the backend sets it.
+  export type Recurrence =
+    | { kind: 'daily'; anchor?: string }
+    | { kind: 'weekly'; anchor?: string }
+    | { kind: 'monthly'; anchor?: string }
+    | { kind: 'cron'; expression: string };
+
   export interface CreateTodoInput {
     title: string;
     description?: string;
     url?: string;
+    status?: TodoStatus;
+    tags?: string[];
+    due_date?: string;
   }
 
   export interface UpdateTodoInput {
@@ -37,6 +57,8 @@ declare namespace I {
     description?: string;
     url?: string;
     status?: TodoStatus;
+    tags?: string[];
+    due_date?: string;
   }
 
   export interface ResearchResult {
@@ -63,4 +85,119 @@ declare namespace I {
     archived: number;
     total: number;
   }
-}
\ No newline at end of file
+
+  export type AppErrorCode =
+    | 'NotFound'
+    | 'InvalidTransition'
+    | 'Validation'
+    | 'Storage'
+    | 'Webview'
+    | 'Provider';
+
+  export interface AppError {
+    code: AppErrorCode;
+    message: string;
+  }
+
+  export type BulkAction =
+    | { type: 'set_status'; status: TodoStatus }
+    | { type: 'add_tags'; tags: string[] }
+    | { type: 'remove_tags'; tags: string[] }
+    | { type: 'archive' }
+    | { type: 'delete' }
+    | { type: 'start_research' };
+
+  export interface BulkItemResult {
+    id: string;
+    success: boolean;
+    todo: Todo | null;
+    error: AppError | null;
+  }
+
+  export interface BulkReport {
+    results: BulkItemResult[];
+    succeeded: number;
+    failed: number;
+  }
+
+  export interface StatusRule {
+    name: string;
+    enabled: boolean;
+    from_status: TodoStatus;
+    to_status: TodoStatus;
+    after_days: number;
+  }
+
+  export interface RuleChange {
+    rule: string;
+    todo_id: string;
+    title: string;
+    from_status: TodoStatus;
+    to_status: TodoStatus;
+  }
+
+  export interface ReminderSettings {
+    todo_id: string;
+    offsets: number[];
+    is_default: boolean;
+    snoozed_until: string | null;
+  }
+
+  export interface QuietHours {
+    start: string;
+    end: string;
+  }
+
+  export interface NotificationPrefs {
+    research_complete: boolean;
+    research_failed: boolean;
+    reminders: boolean;
+    queue_drained: boolean;
+    quiet_hours: QuietHours | null;
+    group_completions: boolean;
+  }
+
+  export interface SettingsChanged {
+    keys: (keyof AppConf)[];
+    settings: AppConf;
+  }
+
+  export interface ThemeChanged {
+    mode: AppConf['theme'];
+    theme: 'light' | 'dark';
+  }
+
+  export type ScriptOrigin = 'bundled' | 'overridden' | 'custom';
+
+  export interface UserScript {
+    file: string;
+    name: string;
+    version: string;
+    url: string;
+    origin: ScriptOrigin;
+    enabled: boolean;
+    default_matches: string[];
+    matches: string[] | null;
+  }
+
+  export interface SelectorPack {
+    name: string;
+    version: string;
+    selectors: Record<string, string[]>;
+  }
+
+  export interface SelftestCheck {
+    key: string;
+    found: boolean;
+    selector: string | null;
+  }
+
+  export interface SelftestReport {
+    url: string;
+    script_loaded: boolean;
+    logged_in: boolean;
+    pack_version: string | null;
+    checks: SelftestCheck[];
+    problems: string[];
+  }
+}
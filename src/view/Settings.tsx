@@ -1,5 +1,122 @@
+import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import clsx from 'clsx';
+
+const CHECK_LABELS: Record<string, string> = {
+  inputBox: '输入框',
+  deepResearchButton: 'Deep Research 按钮',
+  sendButton: '发送按钮',
+  responseContainer: '回复区域',
+};
+
+function StatusRow({ label, ok, detail }: { label: string; ok: boolean; detail?: string | null }) {
+  return (
+    <li className="flex items-center justify-between gap-4 py-1.5 text-sm">
+      <span>{label}</span>
+      <span className={clsx('truncate text-xs', ok ? 'text-green-600' : 'text-red-500')} title={detail || undefined}>
+        {ok ? '✓' : '✗'} {detail || (ok ? '正常' : '未找到')}
+      </span>
+    </li>
+  );
+}
+
 export default function Settings() {
+  const [report, setReport] = useState<I.SelftestReport | null>(null);
+  const [error, setError] = useState<string | null>(null);
+  const [running, setRunning] = useState(false);
+  const [pack, setPack] = useState<I.SelectorPack | null>(null);
+  const [packError, setPackError] = useState<string | null>(null);
+
+  useEffect(() => {
+    invoke<I.SelectorPack>('cmd_get_selector_pack').then(setPack).catch(console.error);
+  }, []);
+
+  const handleLoadPack = async () => {
+    setPackError(null);
+    try {
+      const loaded = await invoke<I.SelectorPack | null>('cmd_load_selector_pack');
+      if (loaded) setPack(loaded);
+    } catch (e: any) {
+      setPackError(e?.message || String(e));
+    }
+  };
+
+  const handleSelftest = async () => {
+    setRunning(true);
+    setError(null);
+    try {
+      setReport(await invoke<I.SelftestReport>('cmd_automation_selftest'));
+    } catch (e: any) {
+      setReport(null);
+      setError(e?.message || String(e));
+    } finally {
+      setRunning(false);
+    }
+  };
+
   return (
-    <div>Settings</div>
-  )
-}
\ No newline at end of file
+    <div className="p-6 text-slate-800 dark:text-slate-200">
+      <section>
+        <div className="flex items-center justify-between">
+          <h2 className="text-base font-semibold">自动化自检</h2>
+          <button
+            className="rounded px-3 py-1 text-sm bg-slate-200 dark:bg-slate-700 disabled:opacity-50"
+            onClick={handleSelftest}
+            disabled={running}
+          >
+            {running ? '检测中...' : '运行自检'}
+          </button>
+        </div>
+        <p className="mt-1 text-xs text-slate-500">
+          检查 ChatGPT 页面上调研所需的元素是否可以找到；每次开始调研前也会自动运行。
+        </p>
+
+        {error && <p className="mt-3 text-sm text-red-500">{error}</p>}
+
+        {report && (
+          <div className="mt-3">
+            <p className={clsx('text-sm', report.problems.length ? 'text-red-500' : 'text-green-600')}>
+              {report.problems.length ? report.problems.join('；') : '一切正常，可以开始调研'}
+            </p>
+            <ul className="mt-2 divide-y divide-slate-200 dark:divide-slate-700">
+              <StatusRow label="自动化脚本" ok={report.script_loaded} />
+              <StatusRow label="登录状态" ok={report.logged_in} detail={report.logged_in ? '已登录' : '未登录'} />
+              {report.checks.map((check) => (
+                <StatusRow
+                  key={check.key}
+                  label={CHECK_LABELS[check.key] || check.key}
+                  ok={check.found}
+                  detail={check.selector}
+                />
+              ))}
+            </ul>
+            <p className="mt-2 text-xs text-slate-500 truncate" title={report.url}>
+              选择器包 {report.pack_version || '内置'} · {report.url}
+            </p>
+          </div>
+        )}
+      </section>
+
+      <section className="mt-8">
+        <div className="flex items-center justify-between">
+          <h2 className="text-base font-semibold">选择器包</h2>
+          <button
+            className="rounded px-3 py-1 text-sm bg-slate-200 dark:bg-slate-700"
+            onClick={handleLoadPack}
+          >
+            加载选择器包...
+          </button>
+        </div>
+        <p className="mt-1 text-xs text-slate-500">
+          ChatGPT 页面改版后，可载入新的选择器包文件，校验通过后立即生效。
+        </p>
+        {pack && (
+          <p className="mt-3 text-sm">
+            当前：{pack.name} {pack.version}
+          </p>
+        )}
+        {packError && <p className="mt-2 text-sm text-red-500">{packError}</p>}
+      </section>
+    </div>
+  );
+}
@@ -1,5 +1,6 @@
 import { useEffect, useState, useMemo } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { getCurrentWindow } from '@tauri-apps/api/window';
 import { openUrl } from '@tauri-apps/plugin-opener';
 import { debounce } from 'lodash';
@@ -34,13 +35,18 @@ export default function Titlebar() {
     let winResize: Function;
     let changeUrl: Function;
 
+    let changeSettings: Function;
+
+    const applyConf = (v: I.AppConf) => {
+      setEnableAsk(v.ask_mode);
+      setPin(v.stay_on_top);
+      setTheme(v.theme);
+      setTitlebarHidden(v.mac_titlebar_hidden);
+    };
+
     invoke<I.AppConf>('get_app_conf')
-      .then((v) => {
-        setEnableAsk(v.ask_mode);
-        setPin(v.stay_on_top);
-        setTheme(v.theme);
-        setTitlebarHidden(v.mac_titlebar_hidden);
-      });
+      .then(applyConf)
+      .catch(console.error);
 
     (async () => {
       const full = await win.isFullscreen();
@@ -50,6 +56,10 @@ export default function Titlebar() {
         setFullScreen(full);
       }, 50))
 
+      changeSettings = await listen<I.SettingsChanged>('settings_changed', (event) => {
+        applyConf(event.payload.settings);
+      });
+
       changeUrl = await getCurrentWindow().listen('navigation:change', (event: any) => {
         const { url } = event.payload;
         setUrl(url);
@@ -66,33 +76,37 @@ export default function Titlebar() {
     return () => {
       winResize && winResize();
       changeUrl && changeUrl();
+      changeSettings && changeSettings();
     }
   }, [])
 
   const handleRefresh = () => {
-    invoke('view_reload');
+    invoke('view_reload').catch(console.error);
   };
 
   const handleGoForward = () => {
-    invoke('view_go_forward');
+    invoke('view_go_forward').catch(console.error);
   };
 
   const handleGoBack = () => {
-    invoke('view_go_back');
+    invoke('view_go_back').catch(console.error);
   };
 
   const handlePin = (isPin: boolean) => {
     setPin(isPin);
-    invoke('window_pin', { pin: isPin });
+    invoke('window_pin', { pin: isPin }).catch((error) => {
+      console.error(error);
+      setPin(!isPin);
+    });
   };
 
   const handleAsk = () => {
     setEnableAsk(!enableAsk);
-    invoke('set_view_ask', { enabled: !enableAsk });
+    invoke('set_view_ask', { enabled: !enableAsk }).catch(console.error);
   };
 
   const handleTheme = (theme: string) => {
-    invoke('set_theme', { theme });
+    invoke('set_theme', { theme }).catch(console.error);
   };
 
   const themeIcon = useMemo(() => {
@@ -1,11 +1,9 @@
 import { useEffect, useState } from 'react';
 import { useTodoStore } from '~/stores/todoStore';
-import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
 import {
   isPermissionGranted,
   requestPermission,
-  sendNotification,
   onAction,
 } from '@tauri-apps/plugin-notification';
 import TodoLayout from './components/TodoLayout';
@@ -17,14 +15,6 @@ import '~/styles/todo.css';
 
 const isTauri = typeof window !== 'undefined' && '__TAURI_INTERNALS__' in window;
 
-interface ResearchCompletePayload {
-  todoId: string;
-  content: string;
-  citations?: { url: string; title: string }[];
-  source: string;
-  startedAt: string;
-}
-
 export default function TodoView() {
   const { 
     fetchTodos, 
@@ -44,49 +34,27 @@ export default function TodoView() {
   useEffect(() => {
     if (!isTauri) return;
 
-    let unlisten: (() => void) | null = null;
+    let unlisteners: (() => void)[] = [];
 
     const setupListener = async () => {
-      const unlistenFn = await listen<ResearchCompletePayload>('research_complete', async (event) => {
-        const { todoId, content, source, startedAt } = event.payload;
-        
-        try {
-          await invoke('cmd_save_research_result', {
-            todoId,
-            source,
-            content,
-            rawHtml: null,
-            startedAt
-          });
-
-          await fetchTodos();
-          await fetchStatusCounts();
-          
-          const todoDetail = await invoke<{ todo: { title: string } } | null>('cmd_get_todo_detail', { id: todoId });
-          const title = todoDetail?.todo?.title || '调研任务';
-
-          const hasPermission = await isPermissionGranted();
-          if (!hasPermission) {
-            const permission = await requestPermission();
-            if (permission !== 'granted') return;
-          }
-
-          sendNotification({
-            title: '调研完成',
-            body: `"${title}" 的调研已完成，点击查看结果`,
-            extra: { todoId }
-          });
-        } catch (err) {
-          console.error('Failed to save research result:', err);
-        }
+      // Notifications are shown by the backend; it only needs permission.
+      if (!(await isPermissionGranted())) {
+        await requestPermission();
+      }
+
+      // Results are saved by the backend; the list only needs refreshing.
+      const unlistenUpdated = await listen<string>('research_updated', async () => {
+        await fetchTodos();
+        await fetchStatusCounts();
       });
-      unlisten = unlistenFn;
+
+      unlisteners = [unlistenUpdated];
     };
 
     setupListener();
 
     return () => {
-      if (unlisten) unlisten();
+      unlisteners.forEach((unlisten) => unlisten());
     };
   }, [fetchTodos, fetchStatusCounts]);
 